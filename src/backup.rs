@@ -0,0 +1,200 @@
+use crate::config::Config;
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const PREFIX: &str = "config-";
+const SUFFIX: &str = ".toml";
+
+pub fn backups_dir() -> PathBuf {
+    Config::path().with_file_name("backups")
+}
+
+/// Replaces `path` with `contents` so that a crash or full disk leaves
+/// either the old file or the new one, never a truncated mix: the data is
+/// written and synced to a temporary file next to it, then renamed over it.
+pub fn write_atomic(path: &Path, contents: &[u8]) -> io::Result<()> {
+    let dir = path.parent().filter(|d| !d.as_os_str().is_empty()).unwrap_or(Path::new("."));
+    let name = path.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default();
+    let tmp = dir.join(format!(".{}.tmp-{}", name, std::process::id()));
+
+    let result = (|| {
+        let mut file = File::create(&tmp)?;
+        if let Ok(metadata) = fs::metadata(path) {
+            file.set_permissions(metadata.permissions())?;
+        }
+        file.write_all(contents)?;
+        file.sync_all()?;
+        fs::rename(&tmp, path)
+    })();
+    if result.is_err() {
+        let _ = fs::remove_file(&tmp);
+    }
+    result?;
+
+    // Makes the rename itself durable.
+    #[cfg(unix)]
+    File::open(dir)?.sync_all()?;
+    Ok(())
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Backup {
+    pub path: PathBuf,
+    /// When it was taken, e.g. `2026-10-18 15:30:00` (UTC).
+    pub created: String,
+    stamp: String,
+    seq: u32,
+}
+
+impl Backup {
+    pub fn name(&self) -> String {
+        self.path.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default()
+    }
+
+    /// Parses `config-20261018-153000.toml`, or `config-20261018-153000-1.toml`
+    /// for a second backup within the same second.
+    fn parse(path: PathBuf) -> Option<Self> {
+        let name = path.file_name()?.to_str()?;
+        let rest = name.strip_prefix(PREFIX)?.strip_suffix(SUFFIX)?;
+        let mut parts = rest.split('-');
+        let (date, time) = (parts.next()?, parts.next()?);
+        let seq = parts.next().map_or(Some(0), |s| s.parse().ok())?;
+        if date.len() != 8 || time.len() != 6 || !date.chars().chain(time.chars()).all(|c| c.is_ascii_digit()) || parts.next().is_some() {
+            return None;
+        }
+        let created = format!("{}-{}-{} {}:{}:{}", &date[..4], &date[4..6], &date[6..], &time[..2], &time[2..4], &time[4..]);
+        Some(Backup { stamp: format!("{}-{}", date, time), seq, created, path })
+    }
+}
+
+/// The backups in `dir`, newest first.
+pub fn list(dir: &Path) -> io::Result<Vec<Backup>> {
+    let mut backups = match fs::read_dir(dir) {
+        Ok(entries) => entries.filter_map(|e| Backup::parse(e.ok()?.path())).collect::<Vec<_>>(),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => vec![],
+        Err(e) => return Err(e),
+    };
+    backups.sort_by(|a, b| (&b.stamp, b.seq).cmp(&(&a.stamp, a.seq)));
+    Ok(backups)
+}
+
+/// Copies `path` into `dir` before it gets overwritten, then deletes all
+/// but the newest `keep` backups. Nothing is copied when `keep` is 0, when
+/// there is no file yet, or when it matches the newest backup.
+pub fn create(path: &Path, dir: &Path, keep: usize) -> io::Result<Option<PathBuf>> {
+    if keep == 0 {
+        return Ok(None);
+    }
+    let contents = match fs::read(path) {
+        Ok(contents) => contents,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(e),
+    };
+    let existing = list(dir)?;
+    if existing.first().is_some_and(|newest| fs::read(&newest.path).is_ok_and(|c| c == contents)) {
+        return Ok(None);
+    }
+
+    // Backups may hold plain text passwords.
+    let mut builder = fs::DirBuilder::new();
+    builder.recursive(true);
+    #[cfg(unix)]
+    std::os::unix::fs::DirBuilderExt::mode(&mut builder, 0o700);
+    builder.create(dir)?;
+    let stamp = timestamp(SystemTime::now());
+    // Numbered after others taken in the same second, so it sorts as newest.
+    let target = match existing.iter().filter(|b| b.stamp == stamp).map(|b| b.seq + 1).max() {
+        Some(seq) => dir.join(format!("{}{}-{}{}", PREFIX, stamp, seq, SUFFIX)),
+        None => dir.join(format!("{}{}{}", PREFIX, stamp, SUFFIX)),
+    };
+    write_atomic(&target, &contents)?;
+
+    for old in list(dir)?.iter().skip(keep) {
+        fs::remove_file(&old.path)?;
+    }
+    Ok(Some(target))
+}
+
+/// Replaces `path` with `backup`, after backing up the current file so
+/// the restore can be undone. A backup that doesn't load is refused.
+pub fn restore(backup: &Path, path: &Path, dir: &Path, keep: usize) -> Result<(), String> {
+    let contents = fs::read_to_string(backup).map_err(|e| e.to_string())?;
+    Config::from_toml(&contents).map_err(|e| e.to_string())?;
+    create(path, dir, keep.max(1)).map_err(|e| e.to_string())?;
+    write_atomic(path, contents.as_bytes()).map_err(|e| e.to_string())
+}
+
+/// `YYYYMMDD-HHMMSS` in UTC.
+fn timestamp(time: SystemTime) -> String {
+    let secs = time.duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0);
+    let (days, rest) = (secs / 86400, secs % 86400);
+    // Howard Hinnant's days-to-civil algorithm.
+    let z = days as i64 + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z - era * 146097;
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    format!("{:04}{:02}{:02}-{:02}{:02}{:02}", year, month, day, rest / 3600, rest % 3600 / 60, rest % 60)
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DiffLine {
+    Same(String),
+    Removed(String),
+    Added(String),
+    /// This many unchanged lines left out.
+    Skipped(usize),
+}
+
+/// A line diff from `old` to `new`, keeping `context` unchanged lines
+/// around each change.
+pub fn diff(old: &str, new: &str, context: usize) -> Vec<DiffLine> {
+    let old: Vec<&str> = old.lines().collect();
+    let new: Vec<&str> = new.lines().collect();
+
+    // Longest common subsequence, filled from the end.
+    let mut lcs = vec![vec![0usize; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            lcs[i][j] = if old[i] == new[j] { lcs[i + 1][j + 1] + 1 } else { lcs[i + 1][j].max(lcs[i][j + 1]) };
+        }
+    }
+    let mut lines = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() || j < new.len() {
+        if i < old.len() && j < new.len() && old[i] == new[j] {
+            lines.push(DiffLine::Same(old[i].to_string()));
+            i += 1;
+            j += 1;
+        } else if i < old.len() && (j == new.len() || lcs[i + 1][j] >= lcs[i][j + 1]) {
+            lines.push(DiffLine::Removed(old[i].to_string()));
+            i += 1;
+        } else {
+            lines.push(DiffLine::Added(new[j].to_string()));
+            j += 1;
+        }
+    }
+
+    let changed: Vec<usize> = lines.iter().enumerate()
+        .filter(|(_, l)| !matches!(l, DiffLine::Same(_)))
+        .map(|(idx, _)| idx)
+        .collect();
+    let near_change = |idx: usize| changed.iter().any(|&c| c.abs_diff(idx) <= context);
+    let mut out = Vec::new();
+    for (idx, line) in lines.into_iter().enumerate() {
+        if near_change(idx) {
+            out.push(line);
+        } else if let Some(DiffLine::Skipped(n)) = out.last_mut() {
+            *n += 1;
+        } else {
+            out.push(DiffLine::Skipped(1));
+        }
+    }
+    out
+}
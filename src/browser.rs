@@ -0,0 +1,388 @@
+use crate::i18n::I18n;
+use crate::sftp::{self, Entry, Session};
+use crossterm::{
+    event::{self, Event, KeyCode, KeyEvent, KeyModifiers},
+    execute,
+    terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
+};
+use tui::{
+    backend::CrosstermBackend,
+    layout::{Constraint, Direction, Layout},
+    style::{Color, Modifier, Style},
+    text::{Span, Spans},
+    widgets::{Block, Borders, List, ListItem, ListState, Paragraph},
+    Terminal,
+};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+const PARENT: &str = "..";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Side {
+    Local,
+    Remote,
+}
+
+/// One side of the browser: a directory and its entries, with `..` first
+/// unless the directory is the root.
+#[derive(Debug, Default)]
+struct Pane {
+    dir: String,
+    entries: Vec<Entry>,
+    cursor: usize,
+}
+
+impl Pane {
+    fn set_entries(&mut self, dir: String, entries: Vec<Entry>, has_parent: bool) {
+        let changed = dir != self.dir;
+        self.dir = dir;
+        self.entries = entries;
+        if has_parent {
+            self.entries.insert(0, Entry { name: PARENT.into(), is_dir: true, is_link: false, size: 0 });
+        }
+        if changed {
+            self.cursor = 0;
+        }
+        self.cursor = self.cursor.min(self.entries.len().saturating_sub(1));
+    }
+
+    fn selected(&self) -> Option<&Entry> {
+        self.entries.get(self.cursor)
+    }
+
+    /// The selected entry, unless it is `..`.
+    fn target(&self) -> Option<&Entry> {
+        self.selected().filter(|e| e.name != PARENT)
+    }
+
+    fn select_name(&mut self, name: &str) {
+        if let Some(pos) = self.entries.iter().position(|e| e.name == name) {
+            self.cursor = pos;
+        }
+    }
+
+    fn move_next(&mut self) {
+        if self.cursor + 1 < self.entries.len() {
+            self.cursor += 1;
+        }
+    }
+
+    fn move_prev(&mut self) {
+        self.cursor = self.cursor.saturating_sub(1);
+    }
+}
+
+enum Prompt {
+    Rename(String),
+    Mkdir(String),
+    ConfirmDelete,
+}
+
+/// Work that runs after the next draw, so the "working" status shows
+/// while a slow copy or delete is in progress.
+enum Op {
+    Copy,
+    Rename(String),
+    Mkdir(String),
+    Delete,
+}
+
+struct Browser<'a> {
+    session: &'a Session,
+    host_name: String,
+    local: Pane,
+    remote: Pane,
+    side: Side,
+    prompt: Option<Prompt>,
+    pending: Option<Op>,
+    status: Result<String, String>,
+}
+
+impl Browser<'_> {
+    fn pane(&mut self) -> &mut Pane {
+        match self.side {
+            Side::Local => &mut self.local,
+            Side::Remote => &mut self.remote,
+        }
+    }
+
+    fn load_local(&mut self, dir: &Path) -> Result<(), String> {
+        let entries = sftp::list_local(dir).map_err(|e| format!("{}: {}", dir.display(), e))?;
+        self.local.set_entries(dir.display().to_string(), entries, dir.parent().is_some());
+        Ok(())
+    }
+
+    fn load_remote(&mut self, dir: &str) -> Result<(), String> {
+        let entries = self.session.list(dir)?;
+        self.remote.set_entries(dir.to_string(), entries, dir != "/");
+        Ok(())
+    }
+
+    fn reload(&mut self) -> Result<(), String> {
+        let local = PathBuf::from(&self.local.dir);
+        let remote = self.remote.dir.clone();
+        self.load_local(&local)?;
+        self.load_remote(&remote)
+    }
+
+    /// Opens the selected directory, or the parent for `..`.
+    fn open(&mut self) {
+        let Some(entry) = self.pane().selected().cloned() else { return };
+        if entry.name == PARENT {
+            self.parent();
+            return;
+        }
+        // Remote symlinks may point at directories, so they are tried too.
+        let result = match self.side {
+            Side::Local if entry.is_dir => self.load_local(&Path::new(&self.local.dir).join(&entry.name)),
+            Side::Remote if entry.is_dir || entry.is_link => self.load_remote(&sftp::remote_join(&self.remote.dir, &entry.name)),
+            _ => return,
+        };
+        if let Err(e) = result {
+            self.status = Err(e);
+        }
+    }
+
+    fn parent(&mut self) {
+        let (result, came_from) = match self.side {
+            Side::Local => {
+                let dir = PathBuf::from(&self.local.dir);
+                let Some(parent) = dir.parent() else { return };
+                let came_from = dir.file_name().map(|n| n.to_string_lossy().into_owned());
+                (self.load_local(parent), came_from)
+            }
+            Side::Remote => {
+                if self.remote.dir == "/" {
+                    return;
+                }
+                let came_from = self.remote.dir.trim_end_matches('/').rsplit('/').next().map(String::from);
+                let parent = sftp::remote_parent(&self.remote.dir);
+                (self.load_remote(&parent), came_from)
+            }
+        };
+        match result {
+            Ok(()) => {
+                if let Some(name) = came_from {
+                    self.pane().select_name(&name);
+                }
+            }
+            Err(e) => self.status = Err(e),
+        }
+    }
+
+    fn perform(&mut self, op: Op, i18n: &I18n) -> Result<String, String> {
+        let local_dir = PathBuf::from(&self.local.dir);
+        let remote_dir = self.remote.dir.clone();
+        let entry = self.pane().target().cloned();
+        let result = match (op, self.side, entry) {
+            (Op::Mkdir(name), Side::Local, _) => fs::create_dir(local_dir.join(&name))
+                .map_err(|err| err.to_string())
+                .map(|_| i18n.browser_created(&name)),
+            (Op::Mkdir(name), Side::Remote, _) => self.session.mkdir(&sftp::remote_join(&remote_dir, &name))
+                .map(|_| i18n.browser_created(&name)),
+            (_, _, None) => return Ok(String::new()),
+            (Op::Copy, Side::Local, Some(e)) => self.session.upload(&local_dir.join(&e.name), &sftp::remote_join(&remote_dir, &e.name))
+                .map(|_| i18n.browser_copied(&e.name, &format!("{}:{}", self.host_name, remote_dir))),
+            (Op::Copy, Side::Remote, Some(e)) => self.session.download(&sftp::remote_join(&remote_dir, &e.name), &local_dir.join(&e.name))
+                .map(|_| i18n.browser_copied(&e.name, &local_dir.display().to_string())),
+            (Op::Rename(to), Side::Local, Some(e)) => fs::rename(local_dir.join(&e.name), local_dir.join(&to))
+                .map_err(|err| err.to_string())
+                .map(|_| i18n.browser_renamed(&e.name, &to)),
+            (Op::Rename(to), Side::Remote, Some(e)) => self.session.rename(&sftp::remote_join(&remote_dir, &e.name), &sftp::remote_join(&remote_dir, &to))
+                .map(|_| i18n.browser_renamed(&e.name, &to)),
+            (Op::Delete, Side::Local, Some(e)) => {
+                let path = local_dir.join(&e.name);
+                let removed = if e.is_dir && !e.is_link { fs::remove_dir_all(path) } else { fs::remove_file(path) };
+                removed.map_err(|err| err.to_string()).map(|_| i18n.browser_deleted(&e.name))
+            }
+            (Op::Delete, Side::Remote, Some(e)) => self.session.remove(&sftp::remote_join(&remote_dir, &e.name), e.is_dir)
+                .map(|_| i18n.browser_deleted(&e.name)),
+        };
+        self.reload()?;
+        result
+    }
+}
+
+fn exit_tui(terminal: &mut Terminal<CrosstermBackend<io::Stdout>>) -> io::Result<()> {
+    disable_raw_mode()?;
+    execute!(terminal.backend_mut(), LeaveAlternateScreen)?;
+    terminal.show_cursor()?;
+    Ok(())
+}
+
+fn pane_items(pane: &Pane) -> Vec<ListItem<'static>> {
+    pane.entries.iter().map(|e| {
+        let (icon, suffix) = match (e.is_dir, e.is_link) {
+            (_, true) => ("🔗 ", "@"),
+            (true, false) => ("📁 ", "/"),
+            (false, false) => ("📄 ", ""),
+        };
+        let mut spans = vec![Span::raw(format!("{}{}{}", icon, e.name, if e.name == PARENT { "" } else { suffix }))];
+        if !e.is_dir && !e.is_link {
+            spans.push(Span::styled(format!("  {}", sftp::human_size(e.size)), Style::default().fg(Color::DarkGray)));
+        }
+        ListItem::new(Spans::from(spans))
+    }).collect()
+}
+
+fn pane_widget<'a>(pane: &Pane, title: String, active: bool) -> List<'a> {
+    let color = if active { Color::Cyan } else { Color::DarkGray };
+    List::new(pane_items(pane))
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(color))
+                .title(Span::styled(title, Style::default().fg(color).add_modifier(Modifier::BOLD)))
+        )
+        .highlight_style(
+            Style::default()
+                .bg(if active { Color::Cyan } else { Color::DarkGray })
+                .fg(Color::Black)
+                .add_modifier(Modifier::BOLD)
+        )
+        .highlight_symbol("▶ ")
+}
+
+/// Shows the local directory `local_dir` next to the session's remote home
+/// until the user leaves with `q` or `Esc`.
+pub fn run(session: &Session, host_name: &str, local_dir: &Path, i18n: &I18n) -> io::Result<()> {
+    let mut browser = Browser {
+        session,
+        host_name: host_name.to_string(),
+        local: Pane::default(),
+        remote: Pane::default(),
+        side: Side::Local,
+        prompt: None,
+        pending: None,
+        status: Ok(String::new()),
+    };
+    if let Err(e) = browser.load_local(local_dir).and_then(|_| browser.load_remote(&session.home)) {
+        return Err(io::Error::other(e));
+    }
+
+    enable_raw_mode()?;
+    let mut stdout = io::stdout();
+    execute!(stdout, EnterAlternateScreen)?;
+    let backend = CrosstermBackend::new(stdout);
+    let mut terminal = Terminal::new(backend)?;
+
+    loop {
+        terminal.draw(|f| {
+            let chunks = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([Constraint::Min(1), Constraint::Length(1), Constraint::Length(3)].as_ref())
+                .split(f.size());
+            let panes = Layout::default()
+                .direction(Direction::Horizontal)
+                .constraints([Constraint::Percentage(50), Constraint::Percentage(50)].as_ref())
+                .split(chunks[0]);
+
+            let mut local_state = ListState::default();
+            local_state.select(Some(browser.local.cursor));
+            let local = pane_widget(&browser.local, format!("💻 Local: {}", browser.local.dir), browser.side == Side::Local);
+            f.render_stateful_widget(local, panes[0], &mut local_state);
+
+            let mut remote_state = ListState::default();
+            remote_state.select(Some(browser.remote.cursor));
+            let remote = pane_widget(&browser.remote, format!("🌐 {}: {}", browser.host_name, browser.remote.dir), browser.side == Side::Remote);
+            f.render_stateful_widget(remote, panes[1], &mut remote_state);
+
+            let target = match browser.side {
+                Side::Local => browser.local.target(),
+                Side::Remote => browser.remote.target(),
+            }.map(|e| e.name.clone()).unwrap_or_default();
+            let status = match (&browser.prompt, &browser.pending, &browser.status) {
+                (Some(Prompt::Rename(input)), _, _) => Span::styled(format!(" {}{}▊", i18n.browser_rename_prompt(&target), input), Style::default().fg(Color::Yellow)),
+                (Some(Prompt::Mkdir(input)), _, _) => Span::styled(format!(" {}{}▊", i18n.browser_mkdir_prompt(), input), Style::default().fg(Color::Yellow)),
+                (Some(Prompt::ConfirmDelete), _, _) => Span::styled(format!(" {}", i18n.browser_confirm_delete(&target)), Style::default().fg(Color::Red).add_modifier(Modifier::BOLD)),
+                (None, Some(_), _) => Span::styled(format!(" {}", i18n.browser_working()), Style::default().fg(Color::Yellow)),
+                (None, None, Ok(message)) => Span::styled(format!(" {}", message), Style::default().fg(Color::Green)),
+                (None, None, Err(error)) => Span::styled(format!(" ❌ {}", error), Style::default().fg(Color::Red)),
+            };
+            f.render_widget(Paragraph::new(Spans::from(vec![status])), chunks[1]);
+
+            let help = Paragraph::new("  ↑/↓/j/k: Navigate  │  ←/h: Parent  │  →/l/Enter: Open  │  Tab: Switch Pane  │  c: Copy to Other Pane  │  r: Rename  │  d: Delete  │  m: New Folder  │  q/Esc: Back")
+                .style(Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD))
+                .block(
+                    Block::default()
+                        .borders(Borders::ALL)
+                        .border_style(Style::default().fg(Color::Cyan))
+                        .title(Spans::from(vec![
+                            Span::styled("⌨️  Keyboard Shortcuts", Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD))
+                        ]))
+                );
+            f.render_widget(help, chunks[2]);
+        })?;
+
+        if let Some(op) = browser.pending.take() {
+            browser.status = browser.perform(op, i18n);
+            continue;
+        }
+
+        if !event::poll(Duration::from_millis(100))? {
+            continue;
+        }
+        let Event::Key(KeyEvent { code, modifiers, .. }) = event::read()? else { continue };
+        if code == KeyCode::Char('c') && modifiers.contains(KeyModifiers::CONTROL) {
+            break;
+        }
+
+        if let Some(prompt) = browser.prompt.as_mut() {
+            match (prompt, code) {
+                (Prompt::ConfirmDelete, KeyCode::Char('y') | KeyCode::Char('Y')) => {
+                    browser.prompt = None;
+                    browser.pending = Some(Op::Delete);
+                }
+                (Prompt::ConfirmDelete, _) | (_, KeyCode::Esc) => browser.prompt = None,
+                (Prompt::Rename(input) | Prompt::Mkdir(input), KeyCode::Backspace) => {
+                    input.pop();
+                }
+                (Prompt::Rename(input) | Prompt::Mkdir(input), KeyCode::Char(c)) => input.push(c),
+                (Prompt::Rename(input), KeyCode::Enter) => {
+                    let name = input.trim().to_string();
+                    browser.prompt = None;
+                    if !name.is_empty() {
+                        browser.pending = Some(Op::Rename(name));
+                    }
+                }
+                (Prompt::Mkdir(input), KeyCode::Enter) => {
+                    let name = input.trim().to_string();
+                    browser.prompt = None;
+                    if !name.is_empty() {
+                        browser.pending = Some(Op::Mkdir(name));
+                    }
+                }
+                _ => {}
+            }
+            continue;
+        }
+
+        match code {
+            KeyCode::Char('q') | KeyCode::Esc => break,
+            KeyCode::Down | KeyCode::Char('j') => browser.pane().move_next(),
+            KeyCode::Up | KeyCode::Char('k') => browser.pane().move_prev(),
+            KeyCode::Left | KeyCode::Char('h') | KeyCode::Backspace => browser.parent(),
+            KeyCode::Right | KeyCode::Char('l') | KeyCode::Enter => browser.open(),
+            KeyCode::Tab | KeyCode::BackTab => {
+                browser.side = match browser.side {
+                    Side::Local => Side::Remote,
+                    Side::Remote => Side::Local,
+                };
+            }
+            KeyCode::Char('c') if browser.pane().target().is_some() => browser.pending = Some(Op::Copy),
+            KeyCode::Char('r') => {
+                if let Some(e) = browser.pane().target() {
+                    browser.prompt = Some(Prompt::Rename(e.name.clone()));
+                }
+            }
+            KeyCode::Char('d') if browser.pane().target().is_some() => browser.prompt = Some(Prompt::ConfirmDelete),
+            KeyCode::Char('m') => browser.prompt = Some(Prompt::Mkdir(String::new())),
+            _ => {}
+        }
+    }
+
+    exit_tui(&mut terminal)
+}
@@ -1,21 +1,489 @@
+use crate::backup;
+use crate::migrate;
+use crate::vault::{self, KdfParams, Vault, VaultError};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::fs;
 use std::path::PathBuf;
 use dirs::home_dir;
+use toml_edit::{ArrayOfTables, DocumentMut, Item, Table};
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
 pub struct Host {
     pub name: String,
+    /// Folder in the host list; nested with `/`, e.g. `prod/eu/web`.
+    pub group: Option<String>,
     pub user: String,
     pub host: String,
     pub port: Option<u16>,
     pub password: Option<String>,
     pub command: Option<String>,
+    pub identity_file: Option<String>,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub identities_only: bool,
+    pub jump: Option<String>,
+    /// Multiplexes connections to this host, keeping an idle master open
+    /// this long (ssh's `ControlPersist`, e.g. `10m`).
+    pub control_persist: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub forwards: Vec<Forward>,
+}
+
+impl Host {
+    /// The group split into its folders, ignoring empty segments, so
+    /// `"/prod//eu/"` and `"prod/eu"` land in the same place.
+    pub fn group_path(&self) -> Vec<&str> {
+        self.group.as_deref()
+            .unwrap_or_default()
+            .split('/')
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+            .collect()
+    }
+
+    pub fn has_tag(&self, tag: &str) -> bool {
+        self.tags.iter().any(|t| t.eq_ignore_ascii_case(tag))
+    }
+}
+
+/// Splits tags typed as `db, k8s legacy` and drops duplicates.
+pub fn parse_tags(input: &str) -> Vec<String> {
+    let mut tags: Vec<String> = Vec::new();
+    for tag in input.split(|c: char| c == ',' || c.is_whitespace()).filter(|t| !t.is_empty()) {
+        if !tags.iter().any(|t| t.eq_ignore_ascii_case(tag)) {
+            tags.push(tag.to_string());
+        }
+    }
+    tags
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ForwardKind {
+    Local,
+    Remote,
+    Dynamic,
+}
+
+/// A port forward in ssh's own syntax: `listen` is `[bind_address:]port`
+/// and `target` is `host:hostport` (unused for dynamic/SOCKS forwards).
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct Forward {
+    pub kind: ForwardKind,
+    pub listen: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub target: Option<String>,
+}
+
+impl Forward {
+    /// Parses the form editor syntax: `L 5432:db:5432`, `R 8080:localhost:80`
+    /// or `D 1080`. A leading dash (`-L`) is accepted too.
+    pub fn parse(line: &str) -> Option<Self> {
+        let line = line.trim().trim_start_matches('-');
+        let mut chars = line.chars();
+        let kind = match chars.next()?.to_ascii_uppercase() {
+            'L' => ForwardKind::Local,
+            'R' => ForwardKind::Remote,
+            'D' => ForwardKind::Dynamic,
+            _ => return None,
+        };
+        let spec = chars.as_str().trim();
+        if spec.is_empty() || spec.contains(char::is_whitespace) {
+            return None;
+        }
+        if kind == ForwardKind::Dynamic {
+            return Some(Forward { kind, listen: spec.to_string(), target: None });
+        }
+        let parts: Vec<&str> = spec.split(':').collect();
+        if parts.len() < 3 || parts.iter().any(|p| p.is_empty()) {
+            return None;
+        }
+        let split = parts.len() - 2;
+        Some(Forward {
+            kind,
+            listen: parts[..split].join(":"),
+            target: Some(parts[split..].join(":")),
+        })
+    }
+
+    pub fn flag(&self) -> &'static str {
+        match self.kind {
+            ForwardKind::Local => "-L",
+            ForwardKind::Remote => "-R",
+            ForwardKind::Dynamic => "-D",
+        }
+    }
+
+    /// The argument that follows [`Forward::flag`] on the ssh command line.
+    pub fn spec(&self) -> String {
+        match &self.target {
+            Some(target) => format!("{}:{}", self.listen, target),
+            None => self.listen.clone(),
+        }
+    }
+}
+
+impl std::fmt::Display for Forward {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {}", &self.flag()[1..], self.spec())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JumpHop {
+    /// Set when the hop refers to another lazyssh host.
+    pub name: Option<String>,
+    /// `[user@]host[:port]` as passed to `ssh -J`.
+    pub spec: String,
+}
+
+impl JumpHop {
+    pub fn label(&self) -> &str {
+        self.name.as_deref().unwrap_or(&self.spec)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum JumpError {
+    /// The chain of host names that leads back to itself.
+    Cycle(Vec<String>),
+    /// A named hop with its own identity file, which `ssh -J` can't use.
+    HopIdentity(String),
+    /// A named hop with its own password, which would have to be typed in
+    /// or sent to the wrong host.
+    HopPassword(String),
+}
+
+impl std::fmt::Display for JumpError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            JumpError::Cycle(names) => write!(f, "jump host cycle: {}", names.join(" → ")),
+            JumpError::HopIdentity(name) => write!(f, "jump host `{}` has its own identity file, which ssh -J can't use; load the key into ssh-agent and clear it", name),
+            JumpError::HopPassword(name) => write!(f, "jump host `{}` has a password, which can't be passed to ssh -J; use a key for it instead", name),
+        }
+    }
+}
+
+/// Expands `h.jump` into the hops ssh has to go through, outermost first.
+/// Entries naming another host are replaced by that host's own route
+/// followed by the host itself; anything else is passed to ssh verbatim.
+pub fn jump_route(hosts: &[Host], h: &Host) -> Result<Vec<JumpHop>, JumpError> {
+    let mut visiting = vec![h.name.clone()];
+    let mut route = Vec::new();
+    if let Some(jump) = &h.jump {
+        expand_jump(hosts, jump, &mut visiting, &mut route)?;
+    }
+    Ok(route)
+}
+
+/// Returns a copy of `h` whose `jump` is the flattened `-J` chain. `-J`
+/// only carries `user@host:port`, so named hops that need their own key or
+/// password are refused rather than connected to without them.
+pub fn resolve_jump(hosts: &[Host], h: &Host) -> Result<Host, JumpError> {
+    let route = jump_route(hosts, h)?;
+    for name in route.iter().filter_map(|hop| hop.name.as_ref()) {
+        let Some(hop) = hosts.iter().find(|h| &h.name == name) else { continue };
+        if hop.identity_file.is_some() {
+            return Err(JumpError::HopIdentity(name.clone()));
+        }
+        if hop.password.is_some() {
+            return Err(JumpError::HopPassword(name.clone()));
+        }
+    }
+    let mut resolved = h.clone();
+    resolved.jump = if route.is_empty() {
+        None
+    } else {
+        Some(route.iter().map(|hop| hop.spec.as_str()).collect::<Vec<_>>().join(","))
+    };
+    Ok(resolved)
+}
+
+fn expand_jump(hosts: &[Host], spec: &str, visiting: &mut Vec<String>, route: &mut Vec<JumpHop>) -> Result<(), JumpError> {
+    for part in spec.split(',').map(str::trim).filter(|p| !p.is_empty()) {
+        let Some(target) = hosts.iter().find(|h| h.name == part) else {
+            route.push(JumpHop { name: None, spec: part.to_string() });
+            continue;
+        };
+        if visiting.contains(&target.name) {
+            let mut cycle = visiting.clone();
+            cycle.push(target.name.clone());
+            return Err(JumpError::Cycle(cycle));
+        }
+        visiting.push(target.name.clone());
+        if let Some(jump) = &target.jump {
+            expand_jump(hosts, jump, visiting, route)?;
+        }
+        visiting.pop();
+
+        // IPv6 addresses need brackets to be told apart from the port.
+        let address = if target.host.contains(':') && !target.host.starts_with('[') {
+            format!("[{}]", target.host)
+        } else {
+            target.host.clone()
+        };
+        let mut hop = if target.user.is_empty() {
+            address
+        } else {
+            format!("{}@{}", target.user, address)
+        };
+        if let Some(port) = target.port {
+            hop.push_str(&format!(":{}", port));
+        }
+        route.push(JumpHop { name: Some(target.name.clone()), spec: hop });
+    }
+    Ok(())
+}
+
+/// `[health_check]` in config.toml.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+#[serde(default)]
+pub struct HealthCheck {
+    /// Seconds between checks; 0, the default, turns the checker off.
+    pub interval: u64,
+    /// Seconds to wait for a connection, and then for the SSH banner.
+    pub timeout: u64,
+    pub read_banner: bool,
+}
+
+impl Default for HealthCheck {
+    fn default() -> Self {
+        HealthCheck { interval: 0, timeout: 3, read_banner: false }
+    }
+}
+
+impl HealthCheck {
+    fn is_default(&self) -> bool {
+        *self == HealthCheck::default()
+    }
+}
+
+/// `[multiplex]` in config.toml.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+#[serde(default)]
+pub struct Multiplex {
+    /// Multiplexes every host, not only those with their own `control_persist`.
+    pub enabled: bool,
+    pub control_persist: String,
+}
+
+impl Default for Multiplex {
+    fn default() -> Self {
+        Multiplex { enabled: false, control_persist: "10m".to_string() }
+    }
+}
+
+impl Multiplex {
+    fn is_default(&self) -> bool {
+        *self == Multiplex::default()
+    }
+
+    /// The `ControlPersist` to use for `h`, or `None` when it isn't multiplexed.
+    pub fn persist_for<'a>(&'a self, h: &'a Host) -> Option<&'a str> {
+        match &h.control_persist {
+            Some(persist) => Some(persist),
+            None if self.enabled => Some(&self.control_persist),
+            None => None,
+        }
+    }
+}
+
+/// `[backups]` in config.toml.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+#[serde(default)]
+pub struct Backups {
+    /// How many copies of config.toml to keep in `~/.lazyssh/backups/`; 0
+    /// turns backups off.
+    pub keep: usize,
+}
+
+impl Default for Backups {
+    fn default() -> Self {
+        Backups { keep: 10 }
+    }
+}
+
+impl Backups {
+    fn is_default(&self) -> bool {
+        *self == Backups::default()
+    }
+}
+
+/// Why config.toml couldn't be loaded, pointing at the offending spot
+/// when there is one.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LoadError {
+    pub message: String,
+    /// 1-based line and column.
+    pub line: Option<usize>,
+    pub column: Option<usize>,
+    /// Path of the offending key, e.g. `hosts[2].port`.
+    pub field: Option<String>,
+    /// The text of the offending line.
+    pub source_line: Option<String>,
+}
+
+impl LoadError {
+    fn read(e: std::io::Error) -> Self {
+        LoadError { message: e.to_string(), line: None, column: None, field: None, source_line: None }
+    }
+
+    /// An error about a top-level key, pointing at its line.
+    fn at_key(source: &str, key: &str, message: String) -> Self {
+        let found = source.lines()
+            .take_while(|l| !l.trim_start().starts_with('['))
+            .enumerate()
+            .find(|(_, l)| l.split_once('=').is_some_and(|(k, _)| k.trim() == key));
+        LoadError {
+            message,
+            line: found.map(|(idx, _)| idx + 1),
+            column: found.map(|(_, l)| l.len() - l.trim_start().len() + 1),
+            field: Some(key.to_string()),
+            source_line: found.map(|(_, l)| l.trim_end_matches('\r').to_string()),
+        }
+    }
+
+    fn parse(source: &str, e: toml::de::Error) -> Self {
+        let message = e.message().to_string();
+        let Some(offset) = e.span().map(|span| span.start.min(source.len())) else {
+            return LoadError { message, line: None, column: None, field: None, source_line: None };
+        };
+        let line_start = source[..offset].rfind('\n').map_or(0, |i| i + 1);
+        let line_end = source[offset..].find('\n').map_or(source.len(), |i| offset + i);
+        let field = field_at(&source[..line_end], &message);
+        LoadError {
+            line: Some(source[..offset].matches('\n').count() + 1),
+            column: Some(source[line_start..offset].chars().count() + 1),
+            field,
+            source_line: Some(source[line_start..line_end].trim_end_matches('\r').to_string()),
+            message,
+        }
+    }
+}
+
+impl std::fmt::Display for LoadError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if let (Some(line), Some(column)) = (self.line, self.column) {
+            write!(f, "line {}, column {}: ", line, column)?;
+        }
+        write!(f, "{}", self.message)?;
+        if let Some(field) = &self.field {
+            write!(f, " (in `{}`)", field)?;
+        }
+        Ok(())
+    }
+}
+
+/// Why `Config::save` left config.toml as it was.
+#[derive(Debug)]
+pub enum SaveError {
+    /// The file on disk doesn't parse, so it was probably edited by hand
+    /// since it was loaded.
+    Invalid(LoadError),
+    Backup(std::io::Error),
+    Serialize(String),
+    Write(std::io::Error),
+}
+
+impl std::fmt::Display for SaveError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SaveError::Invalid(e) => write!(f, "not saving over {}, which has an error: {}", Config::path().display(), e),
+            SaveError::Backup(e) => write!(f, "backing up the previous version failed: {}", e),
+            SaveError::Serialize(e) => write!(f, "serializing failed: {}", e),
+            SaveError::Write(e) => write!(f, "writing failed: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for SaveError {}
+
+/// Works out which key the last line of `source` is about from the table
+/// headers above it: `hosts[1].port` for a bad value, or the table plus
+/// the missing key for serde's "missing field" errors, which point at the
+/// table header.
+fn field_at(source: &str, message: &str) -> Option<String> {
+    let mut arrays: HashMap<String, usize> = HashMap::new();
+    let mut table = String::new();
+    let mut last = "";
+    for line in source.lines() {
+        last = line.trim();
+        let (name, is_array) = if let Some(name) = last.strip_prefix("[[").and_then(|l| l.split("]]").next()) {
+            (name.trim(), true)
+        } else if let Some(name) = last.strip_prefix('[').and_then(|l| l.split(']').next()) {
+            (name.trim(), false)
+        } else {
+            continue;
+        };
+        if is_array {
+            arrays.retain(|array, _| !array.starts_with(&format!("{}.", name)));
+            *arrays.entry(name.to_string()).or_default() += 1;
+        }
+        // `[[hosts.forwards]]` belongs to the latest `[[hosts]]`.
+        let mut path = Vec::new();
+        let mut prefix = String::new();
+        for part in name.split('.') {
+            if !prefix.is_empty() {
+                prefix.push('.');
+            }
+            prefix.push_str(part.trim());
+            match arrays.get(&prefix) {
+                Some(count) => path.push(format!("{}[{}]", part.trim(), count - 1)),
+                None => path.push(part.trim().to_string()),
+            }
+        }
+        table = path.join(".");
+    }
+
+    let key = match message.strip_prefix("missing field `").and_then(|rest| rest.split('`').next()) {
+        Some(missing) => missing,
+        None if last.starts_with('[') => return Some(table).filter(|t| !t.is_empty()),
+        None => last.split('=').next().map(str::trim).filter(|k| !k.is_empty() && !last.starts_with('#'))?,
+    };
+    let key = key.trim_matches('"');
+    Some(if table.is_empty() { key.to_string() } else { format!("{}.{}", table, key) })
+}
+
+#[derive(Debug, Default)]
+pub struct ImportReport {
+    pub added: Vec<String>,
+    pub duplicates: Vec<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Config {
+    /// Format version of the file, see `migrate`. Missing means 0.
+    #[serde(default)]
+    pub version: u32,
     pub hosts: Vec<Host>,
+    #[serde(default, skip_serializing_if = "HealthCheck::is_default")]
+    pub health_check: HealthCheck,
+    #[serde(default, skip_serializing_if = "Multiplex::is_default")]
+    pub multiplex: Multiplex,
+    #[serde(default, skip_serializing_if = "Backups::is_default")]
+    pub backups: Backups,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub vault: Option<Vault>,
+    /// The file as it was loaded, so that saving keeps its comments and
+    /// layout. `hosts[i]` stays the i-th `[[hosts]]` table.
+    #[serde(skip)]
+    pub document: Option<DocumentMut>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            version: migrate::CURRENT,
+            hosts: Vec::new(),
+            health_check: HealthCheck::default(),
+            multiplex: Multiplex::default(),
+            backups: Backups::default(),
+            vault: None,
+            document: None,
+        }
+    }
 }
 
 impl Config {
@@ -27,51 +495,174 @@ impl Config {
         p
     }
 
-    pub fn load() -> Self {
+    pub fn ssh_config_path() -> PathBuf {
+        Self::path().with_file_name("ssh_config")
+    }
+
+    pub fn to_ssh_config(&self) -> String {
+        crate::ssh_config::render(&self.hosts, &self.multiplex)
+    }
+
+    /// Reads config.toml, creating an empty one when there is none. A file
+    /// that can't be read or parsed is an error rather than an empty
+    /// config, so saving can't wipe the hosts in it. A file from an older
+    /// version is upgraded and written back, after backing it up.
+    pub fn load() -> Result<Self, LoadError> {
         let path = Self::path();
         if !path.exists() {
-            let cfg = Config { hosts: vec![] };
-            if let Err(e) = fs::write(&path, toml::to_string_pretty(&cfg).unwrap()) {
+            let cfg = Config::default();
+            if let Err(e) = backup::write_atomic(&path, toml::to_string_pretty(&cfg).unwrap().as_bytes()) {
                 eprintln!("Warning: Failed to create config file: {}", e);
             }
-            return cfg;
-        }
-        
-        match fs::read_to_string(&path) {
-            Ok(s) => {
-                toml::from_str(&s).unwrap_or_else(|e| {
-                    eprintln!("Warning: Failed to parse config file: {}. Using empty config.", e);
-                    Config { hosts: vec![] }
-                })
-            }
-            Err(e) => {
-                eprintln!("Warning: Failed to read config file: {}. Using empty config.", e);
-                Config { hosts: vec![] }
+            return Ok(cfg);
+        }
+        let source = fs::read_to_string(&path).map_err(LoadError::read)?;
+        let Some(upgraded) = migrate::upgrade(&source) else {
+            return Self::from_toml(&source);
+        };
+        // Errors are reported against the file as it is on disk when possible.
+        let cfg = Self::from_toml(&upgraded).map_err(|e| Self::from_toml(&source).err().unwrap_or(e))?;
+        match backup::create(&path, &backup::backups_dir(), cfg.backups.keep.max(1)) {
+            Ok(_) => {
+                if let Err(e) = backup::write_atomic(&path, upgraded.as_bytes()) {
+                    eprintln!("Warning: Failed to write upgraded config file: {}", e);
+                }
             }
+            Err(e) => eprintln!("Warning: Not upgrading config file, backing it up failed: {}", e),
         }
+        Ok(cfg)
     }
 
-    pub fn save(&self) {
+    /// Parses a config without upgrading it. One from a newer version of
+    /// lazyssh is refused, as saving it would drop what this one doesn't know.
+    pub fn from_toml(source: &str) -> Result<Self, LoadError> {
+        let mut cfg: Config = toml::from_str(source).map_err(|e| LoadError::parse(source, e))?;
+        if cfg.version > migrate::CURRENT {
+            let message = format!("version {} is newer than this lazyssh supports ({}), please upgrade lazyssh", cfg.version, migrate::CURRENT);
+            return Err(LoadError::at_key(source, "version", message));
+        }
+        cfg.document = source.parse().ok();
+        Ok(cfg)
+    }
+
+    /// Writes config.toml atomically after backing up the previous version.
+    /// Nothing is written when the file on disk doesn't parse, since
+    /// overwriting it would lose hand edits, or when the backup fails.
+    pub fn save(&mut self) -> Result<(), SaveError> {
         let path = Self::path();
-        match toml::to_string_pretty(self) {
-            Ok(content) => {
-                if let Err(e) = fs::write(&path, content) {
-                    eprintln!("Error: Failed to save config file: {}", e);
+        if let Some(Err(e)) = fs::read_to_string(&path).ok().map(|s| Self::from_toml(&s)) {
+            return Err(SaveError::Invalid(e));
+        }
+        let content = self.to_toml().map_err(|e| SaveError::Serialize(e.to_string()))?;
+        backup::create(&path, &backup::backups_dir(), self.backups.keep).map_err(SaveError::Backup)?;
+        backup::write_atomic(&path, content.as_bytes()).map_err(SaveError::Write)?;
+        self.document = content.parse().ok();
+        Ok(())
+    }
+
+    pub fn is_locked(&self) -> bool {
+        self.vault.as_ref().is_some_and(|v| !v.is_unlocked())
+    }
+
+    /// Unlocks the vault and decrypts stored passwords in memory.
+    /// Passwords that are still plaintext on disk are left as they are and
+    /// get encrypted on the next save. One that doesn't decrypt stays
+    /// encrypted, so it is kept on disk and never sent, and the vault stays
+    /// unlocked for the rest.
+    pub fn unlock(&mut self, passphrase: &str) -> Result<(), VaultError> {
+        let Some(vault) = self.vault.as_mut() else { return Ok(()) };
+        vault.unlock(passphrase)?;
+        let mut corrupted = Vec::new();
+        for host in &mut self.hosts {
+            if let Some(pw) = host.password.as_ref().filter(|pw| vault::is_encrypted(pw)) {
+                match vault.decrypt(pw) {
+                    Ok(plaintext) => host.password = Some(plaintext),
+                    Err(_) => corrupted.push(host.name.clone()),
                 }
             }
-            Err(e) => {
-                eprintln!("Error: Failed to serialize config: {}", e);
+        }
+        if corrupted.is_empty() { Ok(()) } else { Err(VaultError::CorruptedPasswords(corrupted)) }
+    }
+
+    /// Creates a vault protected by `passphrase`, replacing any existing one.
+    /// The current vault must be unlocked so that passwords can be re-encrypted.
+    pub fn set_passphrase(&mut self, passphrase: &str, params: KdfParams) -> Result<(), VaultError> {
+        if self.is_locked() {
+            return Err(VaultError::WrongPassphrase);
+        }
+        self.vault = Some(Vault::create(passphrase, params)?);
+        Ok(())
+    }
+
+    pub fn plaintext_password_count(&self) -> usize {
+        self.hosts.iter()
+            .filter(|h| h.password.as_ref().is_some_and(|pw| !vault::is_encrypted(pw)))
+            .count()
+    }
+
+    fn encrypted(&self) -> Config {
+        let mut cfg = self.clone();
+        if let Some(vault) = &self.vault {
+            for (idx, host) in cfg.hosts.iter_mut().enumerate() {
+                if let Some(pw) = host.password.as_ref().filter(|pw| !vault::is_encrypted(pw)) {
+                    // An unchanged password keeps its ciphertext, so the host's
+                    // table isn't rewritten on every save.
+                    let stored = self.stored_password(idx).filter(|old| vault.decrypt(old).is_ok_and(|p| &p == pw));
+                    if let Some(encrypted) = stored.map(str::to_string).or_else(|| vault.encrypt(pw)) {
+                        host.password = Some(encrypted);
+                    }
+                }
             }
         }
+        cfg
+    }
+
+    fn stored_password(&self, idx: usize) -> Option<&str> {
+        let hosts = self.document.as_ref()?.get("hosts")?.as_array_of_tables()?;
+        hosts.get(idx)?.get("password")?.as_str()
+    }
+
+    /// Serializes the config as it is written to disk, with passwords
+    /// encrypted when a vault is unlocked. A loaded file only has the
+    /// tables and keys that changed rewritten.
+    pub fn to_toml(&self) -> Result<String, toml::ser::Error> {
+        let fresh = toml::to_string_pretty(&self.encrypted())?;
+        Ok(self.document.as_ref().and_then(|doc| merge_document(doc, &fresh)).unwrap_or(fresh))
     }
 
     pub fn add_host(&mut self, host: Host) {
         self.hosts.push(host);
     }
 
+    pub fn host_index(&self, name: &str) -> Option<usize> {
+        self.hosts.iter().position(|h| h.name == name)
+    }
+
+    /// Returns a copy of `h` whose `jump` is the fully resolved `-J` chain.
+    pub fn resolve_jump(&self, h: &Host) -> Result<Host, JumpError> {
+        resolve_jump(&self.hosts, h)
+    }
+
+    pub fn import_hosts(&mut self, hosts: Vec<Host>) -> ImportReport {
+        let mut report = ImportReport::default();
+        for host in hosts {
+            if self.hosts.iter().any(|h| h.name == host.name) {
+                report.duplicates.push(host.name);
+            } else {
+                report.added.push(host.name.clone());
+                self.hosts.push(host);
+            }
+        }
+        report
+    }
+
     pub fn remove_host(&mut self, index: usize) {
         if index < self.hosts.len() {
             self.hosts.remove(index);
+            let tables = self.document.as_mut().and_then(|doc| doc.get_mut("hosts")).and_then(Item::as_array_of_tables_mut);
+            if let Some(tables) = tables.filter(|t| index < t.len()) {
+                remove_table(tables, index);
+            }
         }
     }
 
@@ -82,3 +673,132 @@ impl Config {
     }
 }
 
+/// Writes `fresh` over a copy of `doc`, leaving every table and key whose
+/// value didn't change exactly as it was. `None` when the result wouldn't
+/// load back the same as `fresh`.
+fn merge_document(doc: &DocumentMut, fresh: &str) -> Option<String> {
+    let fresh_doc: DocumentMut = fresh.parse().ok()?;
+    let mut doc = doc.clone();
+    // Top-level keys missing from `fresh` are defaults or unknown, so they stay.
+    for (key, item) in fresh_doc.iter() {
+        match doc.get_mut(key) {
+            Some(existing) if same_setting(key, existing, item) => {}
+            Some(existing) => merge_item(existing, item),
+            None => {
+                doc.insert(key, detached(item));
+            }
+        }
+    }
+    let merged = doc.to_string();
+    let normalized = |s: &str| toml::from_str::<Config>(s).ok().and_then(|c| toml::to_string(&c).ok());
+    (normalized(&merged)? == normalized(fresh)?).then_some(merged)
+}
+
+/// Whether `a` and `b` load as the same top-level setting once defaults
+/// are filled in, e.g. `[health_check]` with or without `timeout = 3`.
+fn same_setting(key: &str, a: &Item, b: &Item) -> bool {
+    let load = |item: &Item| {
+        let mut doc = DocumentMut::new();
+        doc.insert("hosts", toml_edit::value(toml_edit::Array::new()));
+        doc.insert(key, detached(item));
+        toml::from_str::<Config>(&doc.to_string()).ok().and_then(|c| toml::to_string(&c).ok())
+    };
+    load(a).is_some_and(|a| Some(a) == load(b))
+}
+
+fn merge_table(old: &mut Table, new: &Table) {
+    for (key, item) in new.iter() {
+        match old.get_mut(key) {
+            Some(existing) => merge_item(existing, item),
+            None => {
+                old.insert(key, detached(item));
+            }
+        }
+    }
+    old.retain(|key, _| new.contains_key(key));
+}
+
+fn merge_item(old: &mut Item, new: &Item) {
+    if same_value(old, new) {
+        return;
+    }
+    match (&mut *old, new) {
+        (Item::Table(old), Item::Table(new)) => merge_table(old, new),
+        (Item::ArrayOfTables(old), Item::ArrayOfTables(new)) => merge_array(old, new),
+        (Item::Value(old), Item::Value(new)) => {
+            let decor = old.decor().clone();
+            *old = new.clone();
+            *old.decor_mut() = decor;
+        }
+        _ => *old = detached(new),
+    }
+}
+
+/// Tables are matched by index: hosts are only ever appended, updated in
+/// place, or removed from the document along with the config.
+fn merge_array(old: &mut ArrayOfTables, new: &ArrayOfTables) {
+    for (idx, table) in new.iter().enumerate() {
+        match old.get_mut(idx) {
+            Some(existing) => merge_table(existing, table),
+            None => {
+                let mut table = table.clone();
+                detach_table(&mut table);
+                old.push(table);
+            }
+        }
+    }
+    while old.len() > new.len() {
+        old.remove(old.len() - 1);
+    }
+}
+
+/// Removes `tables[index]`. Comments above it that are set apart by a blank
+/// line, like a file or section header, move to the table after it.
+fn remove_table(tables: &mut ArrayOfTables, index: usize) {
+    let prefix = |t: &Table| t.decor().prefix().and_then(|p| p.as_str()).map(str::to_string);
+    let removed = tables.get(index).and_then(prefix).unwrap_or_default();
+    let header = match removed.rfind("\n\n") {
+        Some(end) => &removed[..end + 2],
+        None => &removed[..removed.len() - removed.trim_start_matches('\n').len()],
+    };
+    if let Some(next) = tables.get(index + 1) {
+        let own = prefix(next).unwrap_or_else(|| "\n".to_string());
+        let combined = format!("{}{}", header, own.trim_start_matches('\n'));
+        if let Some(next) = tables.get_mut(index + 1) {
+            next.decor_mut().set_prefix(combined);
+        }
+    }
+    tables.remove(index);
+}
+
+fn same_value(a: &Item, b: &Item) -> bool {
+    let parse = |item: &Item| {
+        let mut doc = DocumentMut::new();
+        doc.insert("v", detached(item));
+        toml::from_str::<toml::Table>(&doc.to_string()).ok()
+    };
+    parse(a).is_some_and(|a| Some(a) == parse(b))
+}
+
+/// A copy of `item` without its position in the document it came from,
+/// so that it is written where it gets inserted.
+fn detached(item: &Item) -> Item {
+    let mut item = item.clone();
+    match &mut item {
+        Item::Table(table) => detach_table(table),
+        Item::ArrayOfTables(tables) => tables.iter_mut().for_each(detach_table),
+        _ => {}
+    }
+    item
+}
+
+fn detach_table(table: &mut Table) {
+    table.set_position(None);
+    for (_, item) in table.iter_mut() {
+        if let Some(nested) = item.as_table_mut() {
+            detach_table(nested);
+        } else if let Some(nested) = item.as_array_of_tables_mut() {
+            nested.iter_mut().for_each(detach_table);
+        }
+    }
+}
@@ -0,0 +1,154 @@
+use crate::config::{self, Host};
+use crate::ssh;
+use crate::ssh_config::wildcard_match;
+use std::io::{BufRead, BufReader, Read};
+use std::process::Stdio;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, Instant};
+
+pub const DEFAULT_JOBS: usize = 8;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExecResult {
+    pub name: String,
+    /// `None` when ssh didn't run or was killed by a signal.
+    pub code: Option<i32>,
+    pub error: Option<String>,
+    pub duration: Duration,
+    /// stdout and stderr lines in the order they arrived.
+    pub output: Vec<String>,
+}
+
+impl ExecResult {
+    pub fn success(&self) -> bool {
+        self.code == Some(0)
+    }
+}
+
+/// Picks hosts by a comma separated list of patterns: a name wildcard
+/// (`web-*`), `group:PATH` for a group and its subgroups, or `tag:NAME`.
+pub fn select_hosts<'a>(hosts: &'a [Host], pattern: &str) -> Vec<&'a Host> {
+    let patterns: Vec<&str> = pattern.split(',').map(str::trim).filter(|p| !p.is_empty()).collect();
+    hosts.iter()
+        .filter(|h| patterns.iter().any(|p| host_matches(h, p)))
+        .collect()
+}
+
+fn host_matches(h: &Host, pattern: &str) -> bool {
+    if let Some(tag) = pattern.strip_prefix("tag:") {
+        return h.has_tag(tag);
+    }
+    if let Some(group) = pattern.strip_prefix("group:") {
+        let wanted: Vec<&str> = group.split('/').filter(|s| !s.is_empty()).collect();
+        return h.group_path().starts_with(&wanted);
+    }
+    wildcard_match(pattern, &h.name)
+}
+
+/// Runs `command` on every target with at most `jobs` ssh processes at a
+/// time. `on_line` sees each output line as it arrives, tagged with the
+/// host name. Results come back in the order of `targets`. Jump hosts are
+/// resolved against `hosts`.
+pub fn run<F>(targets: &[Host], hosts: &[Host], command: &str, jobs: usize, on_line: F) -> Vec<ExecResult>
+where
+    F: Fn(&str, &str) + Sync,
+{
+    let next = AtomicUsize::new(0);
+    let results: Mutex<Vec<Option<ExecResult>>> = Mutex::new(vec![None; targets.len()]);
+
+    thread::scope(|scope| {
+        for _ in 0..jobs.clamp(1, targets.len().max(1)) {
+            scope.spawn(|| loop {
+                let idx = next.fetch_add(1, Ordering::SeqCst);
+                let Some(target) = targets.get(idx) else { break };
+                let result = run_one(target, hosts, command, &on_line);
+                results.lock().unwrap()[idx] = Some(result);
+            });
+        }
+    });
+
+    results.into_inner().unwrap().into_iter().flatten().collect()
+}
+
+fn run_one<F>(h: &Host, hosts: &[Host], command: &str, on_line: &F) -> ExecResult
+where
+    F: Fn(&str, &str) + Sync,
+{
+    let started = Instant::now();
+    let failed = |error: String| ExecResult {
+        name: h.name.clone(),
+        code: None,
+        error: Some(error),
+        duration: started.elapsed(),
+        output: vec![],
+    };
+
+    let resolved = match config::resolve_jump(hosts, h) {
+        Ok(resolved) => resolved,
+        Err(e) => return failed(e.to_string()),
+    };
+    if resolved.password.is_some() && ssh::password_method().is_none() {
+        return failed("no password helper (OpenSSH 8.4+ or sshpass)".into());
+    }
+
+    let mut cmd = ssh::command("ssh", &resolved, &ssh::exec_args(&resolved, command));
+    cmd.stdin(Stdio::null()).stdout(Stdio::piped()).stderr(Stdio::piped());
+    let mut child = match cmd.spawn() {
+        Ok(child) => child,
+        Err(e) => return failed(e.to_string()),
+    };
+
+    let output = Mutex::new(Vec::new());
+    let stdout = child.stdout.take();
+    let stderr = child.stderr.take();
+    thread::scope(|scope| {
+        if let Some(stdout) = stdout {
+            scope.spawn(|| forward_lines(stdout, &h.name, &output, on_line));
+        }
+        if let Some(stderr) = stderr {
+            scope.spawn(|| forward_lines(stderr, &h.name, &output, on_line));
+        }
+    });
+
+    match child.wait() {
+        Ok(status) => ExecResult {
+            name: h.name.clone(),
+            code: status.code(),
+            error: None,
+            duration: started.elapsed(),
+            output: output.into_inner().unwrap(),
+        },
+        Err(e) => failed(e.to_string()),
+    }
+}
+
+fn forward_lines<F>(stream: impl Read, name: &str, output: &Mutex<Vec<String>>, on_line: &F)
+where
+    F: Fn(&str, &str) + Sync,
+{
+    for line in BufReader::new(stream).lines().map_while(Result::ok) {
+        on_line(name, &line);
+        output.lock().unwrap().push(line);
+    }
+}
+
+/// A `HOST  EXIT  TIME` table, one row per result. Hosts that couldn't
+/// be reached through ssh at all get the reason after the time.
+pub fn summary_table(results: &[ExecResult]) -> String {
+    let name_width = results.iter().map(|r| r.name.chars().count()).chain([4]).max().unwrap_or(4);
+    let mut out = format!("{:name_width$}  {:>6}  {:>8}\n", "HOST", "EXIT", "TIME", name_width = name_width);
+    for r in results {
+        let exit = match (&r.error, r.code) {
+            (Some(_), _) => "-".to_string(),
+            (None, Some(code)) => code.to_string(),
+            (None, None) => "signal".to_string(),
+        };
+        let time = format!("{:.2}s", r.duration.as_secs_f64());
+        let line = format!("{:name_width$}  {:>6}  {:>8}  {}", r.name, exit, time, r.error.as_deref().unwrap_or_default(), name_width = name_width);
+        out.push_str(line.trim_end());
+        out.push('\n');
+    }
+    out
+}
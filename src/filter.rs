@@ -0,0 +1,126 @@
+use crate::config::Host;
+
+/// A host that matched every term of a filter query. Positions are char
+/// indices into `name` and `host`, for highlighting in the list.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HostMatch {
+    pub idx: usize,
+    pub score: i64,
+    pub name_positions: Vec<usize>,
+    pub host_positions: Vec<usize>,
+}
+
+/// Matches `hosts` against `query`, best match first. Each whitespace
+/// separated term has to fuzzy-match the name, user, host or command,
+/// except `tag:NAME`, which requires that tag, and `!tag:NAME`, which
+/// excludes it.
+pub fn filter_hosts(hosts: &[Host], query: &str) -> Vec<HostMatch> {
+    let mut matches: Vec<HostMatch> = hosts.iter()
+        .enumerate()
+        .filter_map(|(idx, h)| match_host(idx, h, query))
+        .collect();
+    // Stable, so equal scores keep config order.
+    matches.sort_by_key(|m| std::cmp::Reverse(m.score));
+    matches
+}
+
+pub fn match_host(idx: usize, h: &Host, query: &str) -> Option<HostMatch> {
+    let mut result = HostMatch { idx, score: 0, name_positions: vec![], host_positions: vec![] };
+    for term in query.split_whitespace() {
+        // A bare `tag:` is ignored so the list doesn't blank out mid-typing.
+        if let Some(tag) = term.strip_prefix("!tag:") {
+            if !tag.is_empty() && h.has_tag(tag) {
+                return None;
+            }
+            continue;
+        }
+        if let Some(tag) = term.strip_prefix("tag:") {
+            if !tag.is_empty() && !h.has_tag(tag) {
+                return None;
+            }
+            continue;
+        }
+        let fields = [
+            (Field::Name, h.name.as_str()),
+            (Field::Host, h.host.as_str()),
+            (Field::Other, h.user.as_str()),
+            (Field::Other, h.command.as_deref().unwrap_or_default()),
+        ];
+        let (field, score, positions) = fields.iter()
+            .filter_map(|(field, text)| fuzzy_match(term, text).map(|(score, positions)| (*field, score, positions)))
+            .max_by_key(|(_, score, _)| *score)?;
+        result.score += score;
+        match field {
+            Field::Name => result.name_positions.extend(positions),
+            Field::Host => result.host_positions.extend(positions),
+            Field::Other => {}
+        }
+    }
+    result.name_positions.sort_unstable();
+    result.name_positions.dedup();
+    result.host_positions.sort_unstable();
+    result.host_positions.dedup();
+    Some(result)
+}
+
+#[derive(Clone, Copy)]
+enum Field {
+    Name,
+    Host,
+    Other,
+}
+
+/// Case-insensitive subsequence match. Returns a score (higher is better)
+/// and the char indices of `text` that matched. Consecutive characters
+/// and characters at the start of a word score higher, so `pw` prefers
+/// `prod-web` over `groupware`.
+pub fn fuzzy_match(pattern: &str, text: &str) -> Option<(i64, Vec<usize>)> {
+    let pattern: Vec<char> = pattern.chars().flat_map(char::to_lowercase).collect();
+    if pattern.is_empty() {
+        return Some((0, vec![]));
+    }
+    let text: Vec<char> = text.chars().flat_map(char::to_lowercase).collect();
+
+    // Try every position the first character matches at and keep the best
+    // greedy run, so an early stray hit doesn't hide a tighter match later.
+    let mut best: Option<(i64, Vec<usize>)> = None;
+    for start in (0..text.len()).filter(|&i| text[i] == pattern[0]) {
+        let Some(candidate) = greedy_from(&pattern, &text, start) else { break };
+        if best.as_ref().is_none_or(|(score, _)| candidate.0 > *score) {
+            best = Some(candidate);
+        }
+    }
+    best
+}
+
+fn greedy_from(pattern: &[char], text: &[char], start: usize) -> Option<(i64, Vec<usize>)> {
+    let mut positions = Vec::with_capacity(pattern.len());
+    let mut ti = start;
+    for &pc in pattern {
+        while ti < text.len() && text[ti] != pc {
+            ti += 1;
+        }
+        if ti == text.len() {
+            return None;
+        }
+        positions.push(ti);
+        ti += 1;
+    }
+
+    let mut score = 0;
+    for (i, &pos) in positions.iter().enumerate() {
+        score += 1;
+        if pos == 0 || !text[pos - 1].is_alphanumeric() {
+            score += 8;
+        }
+        if i > 0 {
+            let gap = pos - positions[i - 1] - 1;
+            if gap == 0 {
+                score += 8;
+            } else {
+                score -= gap.min(10) as i64;
+            }
+        }
+    }
+    Some((score, positions))
+}
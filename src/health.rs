@@ -0,0 +1,168 @@
+use crate::config::{HealthCheck, Host};
+use std::collections::HashMap;
+use std::io::{self, Read};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{mpsc, Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime};
+
+/// How many hosts are probed at the same time.
+const PROBE_JOBS: usize = 8;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Probe {
+    pub latency: Duration,
+    /// The server's identification line, e.g. `SSH-2.0-OpenSSH_9.6`.
+    pub banner: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HealthStatus {
+    /// `host:port` that was probed, so a result isn't shown for a host
+    /// whose address has been edited since.
+    pub target: String,
+    pub result: Result<Probe, String>,
+    /// When the host last answered, kept across failed probes.
+    pub last_seen: Option<SystemTime>,
+}
+
+impl HealthStatus {
+    pub fn is_up(&self) -> bool {
+        self.result.is_ok()
+    }
+}
+
+pub fn target(h: &Host) -> String {
+    format!("{}:{}", h.host, h.port.unwrap_or(22))
+}
+
+/// Hosts behind a jump host usually can't be reached directly, so they
+/// aren't probed.
+pub fn is_checkable(h: &Host) -> bool {
+    h.jump.is_none() && !h.host.is_empty()
+}
+
+/// Times a TCP connect to `host:port`, trying each resolved address in
+/// turn, and optionally reads the SSH banner the server sends first.
+pub fn probe(host: &str, port: u16, timeout: Duration, read_banner: bool) -> Result<Probe, String> {
+    let addrs = (host, port).to_socket_addrs().map_err(|e| e.to_string())?;
+    let mut last_error = io::Error::new(io::ErrorKind::NotFound, "no address");
+    for addr in addrs {
+        let started = Instant::now();
+        match TcpStream::connect_timeout(&addr, timeout) {
+            Ok(stream) => {
+                let latency = started.elapsed();
+                let banner = if read_banner { read_ssh_banner(stream, timeout) } else { None };
+                return Ok(Probe { latency, banner });
+            }
+            Err(e) => last_error = e,
+        }
+    }
+    Err(last_error.to_string())
+}
+
+/// Servers may send other lines before the `SSH-` one (RFC 4253 4.2).
+fn read_ssh_banner(mut stream: TcpStream, timeout: Duration) -> Option<String> {
+    stream.set_read_timeout(Some(timeout)).ok()?;
+    let mut buf = Vec::new();
+    let mut chunk = [0u8; 256];
+    while buf.len() < 4096 {
+        let n = stream.read(&mut chunk).ok()?;
+        if n == 0 {
+            break;
+        }
+        buf.extend_from_slice(&chunk[..n]);
+        let text = String::from_utf8_lossy(&buf);
+        let complete = text.matches('\n').count();
+        if let Some(line) = text.split('\n').take(complete).find(|l| l.starts_with("SSH-")) {
+            return Some(line.trim_end_matches('\r').to_string());
+        }
+    }
+    None
+}
+
+pub fn format_latency(latency: Duration) -> String {
+    let ms = latency.as_millis();
+    if ms >= 1000 {
+        format!("{:.1}s", latency.as_secs_f64())
+    } else {
+        format!("{}ms", ms)
+    }
+}
+
+pub fn format_ago(elapsed: Duration) -> String {
+    match elapsed.as_secs() {
+        s if s < 60 => format!("{}s ago", s),
+        s if s < 3600 => format!("{}m ago", s / 60),
+        s if s < 86400 => format!("{}h ago", s / 3600),
+        s => format!("{}d ago", s / 86400),
+    }
+}
+
+/// Probes every host on a background thread, once right away and then
+/// every `interval` seconds. The UI only reads the cached results, so a
+/// slow or unreachable host never holds up drawing.
+pub struct HealthChecker {
+    results: Arc<Mutex<HashMap<String, HealthStatus>>>,
+    hosts_tx: Option<mpsc::Sender<Vec<Host>>>,
+}
+
+impl HealthChecker {
+    /// Does nothing when `settings.interval` is 0.
+    pub fn start(hosts: &[Host], settings: &HealthCheck) -> Self {
+        let results = Arc::new(Mutex::new(HashMap::new()));
+        if settings.interval == 0 {
+            return HealthChecker { results, hosts_tx: None };
+        }
+        let (hosts_tx, hosts_rx) = mpsc::channel();
+        let shared = Arc::clone(&results);
+        let settings = settings.clone();
+        let mut hosts = hosts.to_vec();
+        thread::spawn(move || loop {
+            check_all(&hosts, &settings, &shared);
+            match hosts_rx.recv_timeout(Duration::from_secs(settings.interval)) {
+                Ok(updated) => hosts = updated,
+                Err(mpsc::RecvTimeoutError::Timeout) => {}
+                Err(mpsc::RecvTimeoutError::Disconnected) => break,
+            }
+        });
+        HealthChecker { results, hosts_tx: Some(hosts_tx) }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.hosts_tx.is_some()
+    }
+
+    /// Replaces the hosts to check and probes them right away.
+    pub fn set_hosts(&self, hosts: &[Host]) {
+        if let Some(tx) = &self.hosts_tx {
+            let _ = tx.send(hosts.to_vec());
+        }
+    }
+
+    /// The last result for `h`, unless its address changed since.
+    pub fn status(&self, h: &Host) -> Option<HealthStatus> {
+        let results = self.results.lock().ok()?;
+        results.get(&h.name).filter(|s| s.target == target(h)).cloned()
+    }
+}
+
+fn check_all(hosts: &[Host], settings: &HealthCheck, results: &Mutex<HashMap<String, HealthStatus>>) {
+    let hosts: Vec<&Host> = hosts.iter().filter(|h| is_checkable(h)).collect();
+    let timeout = Duration::from_secs(settings.timeout.max(1));
+    let next = AtomicUsize::new(0);
+    thread::scope(|scope| {
+        for _ in 0..PROBE_JOBS.min(hosts.len()) {
+            scope.spawn(|| loop {
+                let idx = next.fetch_add(1, Ordering::SeqCst);
+                let Some(h) = hosts.get(idx) else { break };
+                let result = probe(&h.host, h.port.unwrap_or(22), timeout, settings.read_banner);
+                let Ok(mut results) = results.lock() else { break };
+                let previous = results.get(&h.name).filter(|s| s.target == target(h)).and_then(|s| s.last_seen);
+                let last_seen = if result.is_ok() { Some(SystemTime::now()) } else { previous };
+                results.insert(h.name.clone(), HealthStatus { target: target(h), result, last_seen });
+            });
+        }
+    });
+}
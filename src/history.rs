@@ -0,0 +1,75 @@
+use crate::config::Config;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// One ssh session, stored as a line of JSON in `history.jsonl`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Record {
+    pub host: String,
+    /// Unix time the session started, in seconds.
+    pub timestamp: u64,
+    /// Seconds until ssh exited.
+    pub duration: u64,
+    /// `None` when ssh couldn't be run at all.
+    pub exit_code: Option<i32>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct HostStats {
+    pub count: usize,
+    /// Unix time of the latest session, 0 when there is none.
+    pub last: u64,
+    pub last_exit: Option<i32>,
+}
+
+#[derive(Debug, Default)]
+pub struct History {
+    records: Vec<Record>,
+}
+
+pub fn now() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0)
+}
+
+impl History {
+    pub fn path() -> PathBuf {
+        Config::path().with_file_name("history.jsonl")
+    }
+
+    /// Reads the history, skipping lines that don't parse. A missing file
+    /// is an empty history.
+    pub fn load(path: &Path) -> Self {
+        let records = fs::read_to_string(path)
+            .unwrap_or_default()
+            .lines()
+            .filter_map(|line| serde_json::from_str(line).ok())
+            .collect();
+        History { records }
+    }
+
+    /// Appends `record` to the file at `path` and to the loaded history.
+    pub fn append(&mut self, path: &Path, record: Record) -> io::Result<()> {
+        let mut line = serde_json::to_string(&record).map_err(io::Error::other)?;
+        line.push('\n');
+        OpenOptions::new().create(true).append(true).open(path)?.write_all(line.as_bytes())?;
+        self.records.push(record);
+        Ok(())
+    }
+
+    pub fn stats(&self) -> HashMap<String, HostStats> {
+        let mut stats: HashMap<String, HostStats> = HashMap::new();
+        for record in &self.records {
+            let entry = stats.entry(record.host.clone()).or_default();
+            entry.count += 1;
+            if record.timestamp >= entry.last {
+                entry.last = record.timestamp;
+                entry.last_exit = record.exit_code;
+            }
+        }
+        stats
+    }
+}
@@ -91,6 +91,46 @@ impl I18n {
         }
     }
 
+    pub fn help_commands(&self) -> &str {
+        if self.is_chinese {
+            "命令:"
+        } else {
+            "Commands:"
+        }
+    }
+
+    pub fn help_import(&self) -> &str {
+        if self.is_chinese {
+            "从 OpenSSH 配置文件导入主机（默认 ~/.ssh/config）"
+        } else {
+            "Import hosts from an OpenSSH config file (default ~/.ssh/config)"
+        }
+    }
+
+    pub fn help_export(&self) -> &str {
+        if self.is_chinese {
+            "将主机导出为 OpenSSH 配置文件（默认 ~/.lazyssh/ssh_config）"
+        } else {
+            "Export hosts as an OpenSSH config file (default ~/.lazyssh/ssh_config)"
+        }
+    }
+
+    pub fn help_vault_init(&self) -> &str {
+        if self.is_chinese {
+            "创建加密密码库并加密已有的明文密码"
+        } else {
+            "Create an encrypted password vault and encrypt existing plaintext passwords"
+        }
+    }
+
+    pub fn help_vault_change(&self) -> &str {
+        if self.is_chinese {
+            "修改密码库的主密码"
+        } else {
+            "Change the vault master passphrase"
+        }
+    }
+
     pub fn help_no_args(&self) -> &str {
         if self.is_chinese {
             "如果没有指定选项，将启动图形化 TUI 界面。"
@@ -101,17 +141,17 @@ impl I18n {
 
     pub fn sshpass_not_found(&self) -> &str {
         if self.is_chinese {
-            "sshpass 未找到。请手动安装 sshpass（macOS 或 Linux）。"
+            "ssh 版本低于 OpenSSH 8.4 且未找到 sshpass。密码自动登录需要升级 OpenSSH 或手动安装 sshpass。"
         } else {
-            "sshpass not found. Please install it manually for macOS or Linux."
+            "ssh is older than OpenSSH 8.4 and sshpass was not found. Upgrade OpenSSH or install sshpass for password auto-login."
         }
     }
 
     pub fn sshpass_cannot_login(&self) -> &str {
         if self.is_chinese {
-            "sshpass 未找到。无法自动登录。"
+            "OpenSSH 8.4+ 或 sshpass 均不可用。无法自动登录。"
         } else {
-            "sshpass not found. Cannot auto-login."
+            "Neither OpenSSH 8.4+ nor sshpass is available. Cannot auto-login."
         }
     }
 
@@ -202,6 +242,670 @@ impl I18n {
             "Error: Failed to parse clipboard content as valid SSH command format"
         }
     }
+
+    pub fn import_requires_source(&self) -> &str {
+        if self.is_chinese {
+            "错误: import 需要指定来源 (例如 --ssh-config)"
+        } else {
+            "Error: import requires a source (e.g., --ssh-config)"
+        }
+    }
+
+    pub fn import_summary(&self, added: usize, duplicates: usize) -> String {
+        if self.is_chinese {
+            format!("已导入 {} 个主机，跳过 {} 个重复主机", added, duplicates)
+        } else {
+            format!("Imported {} host(s), skipped {} duplicate(s)", added, duplicates)
+        }
+    }
+
+    pub fn import_duplicate(&self, name: &str) -> String {
+        if self.is_chinese {
+            format!("  重复: {} (已存在同名主机)", name)
+        } else {
+            format!("  Duplicate: {} (a host with this name already exists)", name)
+        }
+    }
+
+    pub fn import_read_error(&self, path: &str, e: &str) -> String {
+        if self.is_chinese {
+            format!("错误: 无法读取 {}: {}", path, e)
+        } else {
+            format!("Error: Failed to read {}: {}", path, e)
+        }
+    }
+
+    pub fn export_requires_target(&self) -> &str {
+        if self.is_chinese {
+            "错误: export 需要指定格式 (例如 --ssh-config)"
+        } else {
+            "Error: export requires a format (e.g., --ssh-config)"
+        }
+    }
+
+    pub fn export_summary(&self, count: usize, path: &str) -> String {
+        if self.is_chinese {
+            format!("已导出 {} 个主机到 {}", count, path)
+        } else {
+            format!("Exported {} host(s) to {}", count, path)
+        }
+    }
+
+    pub fn export_include_hint(&self, path: &str) -> String {
+        if self.is_chinese {
+            format!("在 ~/.ssh/config 顶部添加以下行以启用: Include {}", path)
+        } else {
+            format!("Add this line at the top of ~/.ssh/config to use it: Include {}", path)
+        }
+    }
+
+    pub fn export_write_error(&self, path: &str, e: &str) -> String {
+        if self.is_chinese {
+            format!("错误: 无法写入 {}: {}", path, e)
+        } else {
+            format!("Error: Failed to write {}: {}", path, e)
+        }
+    }
+
+    pub fn vault_requires_subcommand(&self) -> &str {
+        if self.is_chinese {
+            "错误: vault 需要子命令 (init 或 change-passphrase)"
+        } else {
+            "Error: vault requires a subcommand (init or change-passphrase)"
+        }
+    }
+
+    pub fn vault_passphrase_prompt(&self) -> &str {
+        if self.is_chinese {
+            "请输入密码库主密码"
+        } else {
+            "Vault passphrase"
+        }
+    }
+
+    pub fn vault_new_passphrase_prompt(&self) -> &str {
+        if self.is_chinese {
+            "新的主密码"
+        } else {
+            "New vault passphrase"
+        }
+    }
+
+    pub fn vault_confirm_passphrase_prompt(&self) -> &str {
+        if self.is_chinese {
+            "再次输入主密码"
+        } else {
+            "Repeat passphrase"
+        }
+    }
+
+    pub fn vault_passphrase_mismatch(&self) -> &str {
+        if self.is_chinese {
+            "两次输入的密码不一致"
+        } else {
+            "Passphrases do not match"
+        }
+    }
+
+    pub fn vault_wrong_passphrase(&self) -> &str {
+        if self.is_chinese {
+            "主密码错误，请重试"
+        } else {
+            "Wrong passphrase, try again"
+        }
+    }
+
+    pub fn vault_already_exists(&self) -> &str {
+        if self.is_chinese {
+            "错误: 密码库已存在。使用 'lazyssh vault change-passphrase' 修改主密码"
+        } else {
+            "Error: A vault already exists. Use 'lazyssh vault change-passphrase' to change it"
+        }
+    }
+
+    pub fn vault_not_initialized(&self) -> &str {
+        if self.is_chinese {
+            "错误: 尚未创建密码库。使用 'lazyssh vault init' 创建"
+        } else {
+            "Error: No vault configured. Use 'lazyssh vault init' to create one"
+        }
+    }
+
+    pub fn vault_created(&self, migrated: usize) -> String {
+        if self.is_chinese {
+            format!("密码库已创建，已加密 {} 个明文密码", migrated)
+        } else {
+            format!("Vault created, {} plaintext password(s) encrypted", migrated)
+        }
+    }
+
+    pub fn vault_passphrase_changed(&self) -> &str {
+        if self.is_chinese {
+            "主密码已修改"
+        } else {
+            "Vault passphrase changed"
+        }
+    }
+
+    pub fn vault_error(&self, e: &str) -> String {
+        if self.is_chinese {
+            format!("错误: 密码库操作失败: {}", e)
+        } else {
+            format!("Error: Vault operation failed: {}", e)
+        }
+    }
+
+    pub fn jump_error(&self, e: &str) -> String {
+        if self.is_chinese {
+            format!("\n❌ 无法解析跳板机: {}", e)
+        } else {
+            format!("\n❌ Failed to resolve jump hosts: {}", e)
+        }
+    }
+
+    pub fn tunnel_open(&self, userhost: &str) -> String {
+        if self.is_chinese {
+            format!("\n🔀 隧道已建立: {}", userhost)
+        } else {
+            format!("\n🔀 Tunnel open: {}", userhost)
+        }
+    }
+
+    pub fn tunnel_press_enter_to_close(&self) -> &str {
+        if self.is_chinese {
+            "\n按回车键关闭隧道..."
+        } else {
+            "\nPress Enter to close the tunnel..."
+        }
+    }
+
+    pub fn tunnel_closed(&self) -> &str {
+        if self.is_chinese {
+            "隧道已关闭"
+        } else {
+            "Tunnel closed"
+        }
+    }
+
+    pub fn tunnel_exited(&self, code: &str) -> String {
+        if self.is_chinese {
+            format!("\n❌ 隧道意外退出 (退出代码: {})。按回车键继续...", code)
+        } else {
+            format!("\n❌ Tunnel exited unexpectedly (exit code: {}). Press Enter to continue...", code)
+        }
+    }
+
+    pub fn help_list(&self) -> &str {
+        if self.is_chinese {
+            "列出所有主机，默认隐藏密码"
+        } else {
+            "List hosts, with passwords redacted unless --show-secrets is given"
+        }
+    }
+
+    pub fn help_add(&self) -> &str {
+        if self.is_chinese {
+            "添加主机（必需 --name、--user 和 --host）"
+        } else {
+            "Add a host (--name, --user and --host are required)"
+        }
+    }
+
+    pub fn help_edit(&self) -> &str {
+        if self.is_chinese {
+            "修改指定主机的字段"
+        } else {
+            "Change fields of the named host"
+        }
+    }
+
+    pub fn help_rm(&self) -> &str {
+        if self.is_chinese {
+            "删除主机"
+        } else {
+            "Remove a host"
+        }
+    }
+
+    pub fn help_connect(&self) -> &str {
+        if self.is_chinese {
+            "连接主机，以 ssh 的退出代码退出"
+        } else {
+            "Connect to a host, exiting with ssh's exit code"
+        }
+    }
+
+    pub fn help_host_options(&self) -> &str {
+        if self.is_chinese {
+            "主机选项 (add / edit):"
+        } else {
+            "Host options (add / edit):"
+        }
+    }
+
+    pub fn cli_requires_host_name(&self, command: &str) -> String {
+        if self.is_chinese {
+            format!("错误: {} 需要主机名称", command)
+        } else {
+            format!("Error: {} requires a host name", command)
+        }
+    }
+
+    pub fn cli_requires_value(&self, flag: &str) -> String {
+        if self.is_chinese {
+            format!("错误: {} 需要一个值", flag)
+        } else {
+            format!("Error: {} requires a value", flag)
+        }
+    }
+
+    pub fn cli_invalid_value(&self, flag: &str, value: &str) -> String {
+        if self.is_chinese {
+            format!("错误: {} 的值无效: {}", flag, value)
+        } else {
+            format!("Error: Invalid value for {}: {}", flag, value)
+        }
+    }
+
+    pub fn cli_missing_fields(&self, fields: &str) -> String {
+        if self.is_chinese {
+            format!("错误: 缺少必需的选项: {}", fields)
+        } else {
+            format!("Error: Missing required options: {}", fields)
+        }
+    }
+
+    pub fn cli_host_not_found(&self, name: &str) -> String {
+        if self.is_chinese {
+            format!("错误: 找不到主机 '{}'", name)
+        } else {
+            format!("Error: No host named '{}'", name)
+        }
+    }
+
+    pub fn cli_host_exists(&self, name: &str) -> String {
+        if self.is_chinese {
+            format!("错误: 主机 '{}' 已存在", name)
+        } else {
+            format!("Error: A host named '{}' already exists", name)
+        }
+    }
+
+    pub fn cli_host_added(&self, name: &str) -> String {
+        if self.is_chinese {
+            format!("已添加主机 '{}'", name)
+        } else {
+            format!("Added host '{}'", name)
+        }
+    }
+
+    pub fn cli_host_updated(&self, name: &str) -> String {
+        if self.is_chinese {
+            format!("已更新主机 '{}'", name)
+        } else {
+            format!("Updated host '{}'", name)
+        }
+    }
+
+    pub fn cli_host_removed(&self, name: &str) -> String {
+        if self.is_chinese {
+            format!("已删除主机 '{}'", name)
+        } else {
+            format!("Removed host '{}'", name)
+        }
+    }
+
+    pub fn cli_no_hosts(&self) -> &str {
+        if self.is_chinese {
+            "尚未配置任何主机"
+        } else {
+            "No hosts configured"
+        }
+    }
+
+    pub fn help_exec(&self) -> &str {
+        if self.is_chinese {
+            "在匹配的主机上并行执行命令（-j 限制并发数，--collect 按主机汇总输出）"
+        } else {
+            "Run a command on matching hosts in parallel (-j limits concurrency, --collect groups output per host)"
+        }
+    }
+
+    pub fn exec_requires_pattern(&self) -> &str {
+        if self.is_chinese {
+            "错误: exec 需要主机匹配模式，例如 lazyssh exec 'web-*' -- uptime"
+        } else {
+            "Error: exec requires a host pattern, e.g. lazyssh exec 'web-*' -- uptime"
+        }
+    }
+
+    pub fn exec_requires_command(&self) -> &str {
+        if self.is_chinese {
+            "错误: 请在 -- 之后给出要执行的命令"
+        } else {
+            "Error: Give the command to run after --"
+        }
+    }
+
+    pub fn exec_no_match(&self, pattern: &str) -> String {
+        if self.is_chinese {
+            format!("错误: 没有主机匹配 '{}'", pattern)
+        } else {
+            format!("Error: No hosts match '{}'", pattern)
+        }
+    }
+
+    pub fn exec_command_prompt(&self) -> &str {
+        if self.is_chinese {
+            "要执行的命令"
+        } else {
+            "Command to run"
+        }
+    }
+
+    pub fn exec_running(&self, command: &str, count: usize, jobs: usize) -> String {
+        if self.is_chinese {
+            format!("\n▶ 在 {1} 台主机上执行 `{0}`（并发 {2}）\n", command, count, jobs)
+        } else {
+            format!("\n▶ Running `{0}` on {1} host(s), {2} at a time\n", command, count, jobs)
+        }
+    }
+
+    pub fn exec_summary(&self, ok: usize, total: usize) -> String {
+        if self.is_chinese {
+            format!("\n{} / {} 台主机执行成功", ok, total)
+        } else {
+            format!("\n{} of {} host(s) succeeded", ok, total)
+        }
+    }
+
+    pub fn confirm_delete_hosts(&self, count: usize) -> String {
+        if self.is_chinese {
+            format!("│  确认删除 {:3} 台主机:                    │", count)
+        } else {
+            format!("│  Confirm delete {:3} hosts:               │", count)
+        }
+    }
+
+    pub fn transfer_upload(&self, from: &str, to: &str) -> String {
+        if self.is_chinese {
+            format!("\n⇡ 上传 {} → {}\n", from, to)
+        } else {
+            format!("\n⇡ Uploading {} → {}\n", from, to)
+        }
+    }
+
+    pub fn transfer_download(&self, from: &str, to: &str) -> String {
+        if self.is_chinese {
+            format!("\n⇣ 下载 {} → {}\n", from, to)
+        } else {
+            format!("\n⇣ Downloading {} → {}\n", from, to)
+        }
+    }
+
+    pub fn transfer_done(&self, secs: f64) -> String {
+        if self.is_chinese {
+            format!("\n✅ 传输完成（{:.1} 秒）", secs)
+        } else {
+            format!("\n✅ Transfer complete ({:.1}s)", secs)
+        }
+    }
+
+    pub fn transfer_failed(&self, code: i32) -> String {
+        if self.is_chinese {
+            format!("\n❌ 传输失败（退出码 {}）", code)
+        } else {
+            format!("\n❌ Transfer failed (exit code {})", code)
+        }
+    }
+
+    pub fn browser_connecting(&self, host: &str) -> String {
+        if self.is_chinese {
+            format!("\n🔌 正在打开 {} 的文件浏览器...", host)
+        } else {
+            format!("\n🔌 Opening file browser on {}...", host)
+        }
+    }
+
+    pub fn browser_error(&self, error: &str) -> String {
+        if self.is_chinese {
+            format!("❌ 文件浏览器出错：{}", error)
+        } else {
+            format!("❌ File browser error: {}", error)
+        }
+    }
+
+    pub fn browser_working(&self) -> &str {
+        if self.is_chinese {
+            "⏳ 处理中..."
+        } else {
+            "⏳ Working..."
+        }
+    }
+
+    pub fn browser_copied(&self, name: &str, to: &str) -> String {
+        if self.is_chinese {
+            format!("✅ 已复制 {} → {}", name, to)
+        } else {
+            format!("✅ Copied {} → {}", name, to)
+        }
+    }
+
+    pub fn browser_renamed(&self, from: &str, to: &str) -> String {
+        if self.is_chinese {
+            format!("✅ 已将 {} 重命名为 {}", from, to)
+        } else {
+            format!("✅ Renamed {} to {}", from, to)
+        }
+    }
+
+    pub fn browser_deleted(&self, name: &str) -> String {
+        if self.is_chinese {
+            format!("✅ 已删除 {}", name)
+        } else {
+            format!("✅ Deleted {}", name)
+        }
+    }
+
+    pub fn browser_created(&self, name: &str) -> String {
+        if self.is_chinese {
+            format!("✅ 已创建文件夹 {}", name)
+        } else {
+            format!("✅ Created folder {}", name)
+        }
+    }
+
+    pub fn browser_rename_prompt(&self, name: &str) -> String {
+        if self.is_chinese {
+            format!("将 {} 重命名为：", name)
+        } else {
+            format!("Rename {} to: ", name)
+        }
+    }
+
+    pub fn browser_mkdir_prompt(&self) -> &str {
+        if self.is_chinese {
+            "新文件夹名称："
+        } else {
+            "New folder name: "
+        }
+    }
+
+    pub fn browser_confirm_delete(&self, name: &str) -> String {
+        if self.is_chinese {
+            format!("删除 {} 及其全部内容？(y/N)", name)
+        } else {
+            format!("Delete {} and everything in it? (y/N)", name)
+        }
+    }
+
+    pub fn history_write_error(&self, error: &str) -> String {
+        if self.is_chinese {
+            format!("⚠️  无法写入连接历史: {}", error)
+        } else {
+            format!("⚠️  Could not write connection history: {}", error)
+        }
+    }
+
+    pub fn master_running(&self, host: &str, reply: &str) -> String {
+        if self.is_chinese {
+            format!("✅ {} 的控制主连接正在运行: {}", host, reply)
+        } else {
+            format!("✅ Control master for {} is running: {}", host, reply)
+        }
+    }
+
+    pub fn master_not_running(&self, host: &str, error: &str) -> String {
+        if self.is_chinese {
+            format!("❌ {} 没有运行中的控制主连接: {}", host, error)
+        } else {
+            format!("❌ No control master running for {}: {}", host, error)
+        }
+    }
+
+    pub fn master_closed(&self, host: &str) -> String {
+        if self.is_chinese {
+            format!("✅ 已关闭 {} 的控制主连接", host)
+        } else {
+            format!("✅ Closed the control master for {}", host)
+        }
+    }
+
+    pub fn master_close_failed(&self, host: &str, error: &str) -> String {
+        if self.is_chinese {
+            format!("❌ 无法关闭 {} 的控制主连接: {}", host, error)
+        } else {
+            format!("❌ Could not close the control master for {}: {}", host, error)
+        }
+    }
+
+    pub fn config_load_error(&self, path: &str, error: &str) -> String {
+        if self.is_chinese {
+            format!("❌ 无法加载配置文件 {}: {}\n   修复后重试，lazyssh 不会覆盖该文件。", path, error)
+        } else {
+            format!("❌ Could not load config file {}: {}\n   Fix it and try again; lazyssh won't write over it.", path, error)
+        }
+    }
+
+    pub fn editor_error(&self, error: &str) -> String {
+        if self.is_chinese {
+            format!("❌ 无法打开编辑器: {}", error)
+        } else {
+            format!("❌ Could not open the editor: {}", error)
+        }
+    }
+
+    pub fn help_config_restore(&self) -> &str {
+        if self.is_chinese {
+            "列出配置备份，或预览差异后恢复其中一个"
+        } else {
+            "List config backups, or restore one after previewing the diff"
+        }
+    }
+
+    pub fn config_requires_subcommand(&self) -> &str {
+        if self.is_chinese {
+            "❌ config 需要子命令: restore"
+        } else {
+            "❌ config needs a subcommand: restore"
+        }
+    }
+
+    pub fn config_no_backups(&self, dir: &str) -> String {
+        if self.is_chinese {
+            format!("{} 中还没有备份", dir)
+        } else {
+            format!("No backups in {} yet", dir)
+        }
+    }
+
+    pub fn config_restore_hint(&self) -> &str {
+        if self.is_chinese {
+            "使用 `lazyssh config restore N` 预览并恢复第 N 个备份"
+        } else {
+            "Run `lazyssh config restore N` to preview and restore backup N"
+        }
+    }
+
+    pub fn config_no_such_backup(&self, backup: &str) -> String {
+        if self.is_chinese {
+            format!("❌ 找不到备份: {}", backup)
+        } else {
+            format!("❌ No such backup: {}", backup)
+        }
+    }
+
+    pub fn config_backup_unchanged(&self) -> &str {
+        if self.is_chinese {
+            "该备份与当前配置相同"
+        } else {
+            "That backup matches the current config"
+        }
+    }
+
+    pub fn config_unchanged_lines(&self, n: usize) -> String {
+        if self.is_chinese {
+            format!("{} 行未改变", n)
+        } else {
+            format!("{} unchanged lines", n)
+        }
+    }
+
+    pub fn config_restore_confirm(&self) -> &str {
+        if self.is_chinese {
+            "恢复此备份？当前配置会先被备份"
+        } else {
+            "Restore this backup? The current config is backed up first"
+        }
+    }
+
+    pub fn config_restored(&self, backup: &str) -> String {
+        if self.is_chinese {
+            format!("✅ 已从 {} 恢复配置", backup)
+        } else {
+            format!("✅ Restored the config from {}", backup)
+        }
+    }
+
+    pub fn config_restore_failed(&self, error: &str) -> String {
+        if self.is_chinese {
+            format!("❌ 恢复失败: {}", error)
+        } else {
+            format!("❌ Restore failed: {}", error)
+        }
+    }
+
+    pub fn vault_invalid_kdf_value(&self, flag: &str, value: &str, min: u32) -> String {
+        if self.is_chinese {
+            format!("错误: {} 的值 \"{}\" 无效: 必须是不小于 {} 的整数", flag, value, min)
+        } else {
+            format!("Error: Invalid value for {}: \"{}\" must be a whole number of at least {}", flag, value, min)
+        }
+    }
+
+    pub fn password_reserved_prefix(&self) -> &str {
+        if self.is_chinese {
+            "错误: 密码不能以 enc:v1: 开头，该前缀用于标记已加密的密码"
+        } else {
+            "Error: Passwords can't start with enc:v1:, which marks encrypted ones"
+        }
+    }
+
+    pub fn config_save_error(&self, error: &str) -> String {
+        if self.is_chinese {
+            format!("❌ 配置未保存: {}", error)
+        } else {
+            format!("❌ Config not saved: {}", error)
+        }
+    }
+
+    pub fn vault_warning(&self, e: &str) -> String {
+        if self.is_chinese {
+            format!("警告: {}", e)
+        } else {
+            format!("Warning: {}", e)
+        }
+    }
 }
 
 impl Default for I18n {
@@ -2,3 +2,17 @@ pub mod config;
 pub mod ui;
 pub mod i18n;
 
+pub mod ssh;
+pub mod ssh_config;
+pub mod vault;
+pub mod tunnel;
+pub mod filter;
+pub mod listing;
+pub mod exec;
+pub mod sftp;
+pub mod backup;
+pub mod browser;
+pub mod health;
+pub mod history;
+pub mod multiplex;
+pub mod migrate;
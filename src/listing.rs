@@ -0,0 +1,154 @@
+use crate::config::{ForwardKind, Host};
+use serde::Serialize;
+
+/// Bumped whenever a field of the JSON output is renamed, removed or
+/// changes meaning. Adding fields doesn't bump it.
+pub const JSON_FORMAT_VERSION: u32 = 1;
+
+pub const REDACTED: &str = "********";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ListFormat {
+    Table,
+    Tsv,
+    Json,
+}
+
+impl ListFormat {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "table" => Some(ListFormat::Table),
+            "tsv" => Some(ListFormat::Tsv),
+            "json" => Some(ListFormat::Json),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct JsonListing<'a> {
+    version: u32,
+    hosts: Vec<JsonHost<'a>>,
+}
+
+/// The JSON shape of a host. Kept separate from `config::Host` so the
+/// config file can evolve without breaking tools that read this output.
+#[derive(Serialize)]
+struct JsonHost<'a> {
+    name: &'a str,
+    user: &'a str,
+    host: &'a str,
+    port: u16,
+    group: Option<String>,
+    tags: &'a [String],
+    identity_file: Option<&'a str>,
+    identities_only: bool,
+    jump: Option<&'a str>,
+    control_persist: Option<&'a str>,
+    forwards: Vec<JsonForward<'a>>,
+    command: Option<&'a str>,
+    password: Option<&'a str>,
+}
+
+#[derive(Serialize)]
+struct JsonForward<'a> {
+    kind: ForwardKind,
+    listen: &'a str,
+    target: Option<&'a str>,
+}
+
+/// Renders `hosts` for `lazyssh list`. Passwords are replaced with
+/// `REDACTED` unless `show_secrets` is set.
+pub fn render(hosts: &[Host], format: ListFormat, show_secrets: bool) -> String {
+    match format {
+        ListFormat::Table => render_table(hosts, show_secrets),
+        ListFormat::Tsv => render_tsv(hosts, show_secrets),
+        ListFormat::Json => render_json(hosts, show_secrets),
+    }
+}
+
+fn password(h: &Host, show_secrets: bool) -> Option<&str> {
+    h.password.as_deref().map(|pw| if show_secrets { pw } else { REDACTED })
+}
+
+fn cells(h: &Host, show_secrets: bool) -> [String; 7] {
+    [
+        h.name.clone(),
+        h.user.clone(),
+        h.host.clone(),
+        h.port.unwrap_or(22).to_string(),
+        h.group_path().join("/"),
+        h.tags.join(","),
+        password(h, show_secrets).unwrap_or_default().to_string(),
+    ]
+}
+
+fn render_table(hosts: &[Host], show_secrets: bool) -> String {
+    let header = ["NAME", "USER", "HOST", "PORT", "GROUP", "TAGS", "PASSWORD"];
+    let rows: Vec<[String; 7]> = hosts.iter().map(|h| cells(h, show_secrets)).collect();
+    let mut widths = header.map(str::len);
+    for row in &rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+
+    let mut out = String::new();
+    let mut push_row = |cells: &[&str]| {
+        let line: Vec<String> = cells.iter()
+            .zip(widths)
+            .map(|(cell, width)| format!("{:width$}", cell, width = width))
+            .collect();
+        out.push_str(line.join("  ").trim_end());
+        out.push('\n');
+    };
+    push_row(&header);
+    for row in &rows {
+        push_row(&row.each_ref().map(String::as_str));
+    }
+    out
+}
+
+/// One host per line, no header, columns in the same order as the table.
+/// Tabs, newlines and backslashes inside values are escaped.
+fn render_tsv(hosts: &[Host], show_secrets: bool) -> String {
+    let mut out = String::new();
+    for h in hosts {
+        let line: Vec<String> = cells(h, show_secrets).iter().map(|c| escape_tsv(c)).collect();
+        out.push_str(&line.join("\t"));
+        out.push('\n');
+    }
+    out
+}
+
+fn escape_tsv(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('\t', "\\t").replace('\n', "\\n").replace('\r', "\\r")
+}
+
+fn render_json(hosts: &[Host], show_secrets: bool) -> String {
+    let listing = JsonListing {
+        version: JSON_FORMAT_VERSION,
+        hosts: hosts.iter().map(|h| JsonHost {
+            name: &h.name,
+            user: &h.user,
+            host: &h.host,
+            port: h.port.unwrap_or(22),
+            group: Some(h.group_path().join("/")).filter(|g| !g.is_empty()),
+            tags: &h.tags,
+            identity_file: h.identity_file.as_deref(),
+            identities_only: h.identities_only,
+            jump: h.jump.as_deref(),
+            control_persist: h.control_persist.as_deref(),
+            forwards: h.forwards.iter().map(|f| JsonForward {
+                kind: f.kind,
+                listen: &f.listen,
+                target: f.target.as_deref(),
+            }).collect(),
+            command: h.command.as_deref(),
+            password: password(h, show_secrets),
+        }).collect(),
+    };
+    let mut out = serde_json::to_string_pretty(&listing).expect("host listing is always serializable");
+    out.push('\n');
+    out
+}
@@ -1,17 +1,38 @@
 mod config;
 mod ui;
 mod i18n;
+mod ssh;
+mod ssh_config;
+mod vault;
+mod tunnel;
+mod filter;
+mod listing;
+mod exec;
+mod sftp;
+mod backup;
+mod browser;
+mod health;
+mod history;
+mod multiplex;
+mod migrate;
 
 use config::{Config, Host};
-use std::process::Command;
+use history::History;
 use std::env;
-use which::which;
+use std::path::{Path, PathBuf};
+use std::time::Instant;
 use i18n::I18n;
+use listing::ListFormat;
+use vault::{KdfParams, VaultError};
 
 const VERSION: &str = env!("CARGO_PKG_VERSION");
 
 fn main() {
     let args: Vec<String> = env::args().collect();
+    if env::var_os(ssh::ASKPASS_ENV).is_some() || args.get(1).map(String::as_str) == Some("--askpass") {
+        std::process::exit(run_askpass(&args));
+    }
+
     let mut lang_override: Option<String> = None;
     let mut i = 1;
     
@@ -35,6 +56,54 @@ fn main() {
                     std::process::exit(1);
                 }
             }
+            "import" => {
+                let i18n = I18n::with_lang(lang_override.as_deref());
+                run_import(&args[i + 1..], &i18n);
+                return;
+            }
+            "export" => {
+                let i18n = I18n::with_lang(lang_override.as_deref());
+                run_export(&args[i + 1..], &i18n);
+                return;
+            }
+            "vault" => {
+                let i18n = I18n::with_lang(lang_override.as_deref());
+                run_vault(&args[i + 1..], &i18n);
+                return;
+            }
+            "config" => {
+                let i18n = I18n::with_lang(lang_override.as_deref());
+                run_config(&args[i + 1..], &i18n);
+                return;
+            }
+            "list" => {
+                let i18n = I18n::with_lang(lang_override.as_deref());
+                run_list(&args[i + 1..], &i18n);
+                return;
+            }
+            "add" => {
+                let i18n = I18n::with_lang(lang_override.as_deref());
+                run_add(&args[i + 1..], &i18n);
+                return;
+            }
+            "edit" => {
+                let i18n = I18n::with_lang(lang_override.as_deref());
+                run_edit(&args[i + 1..], &i18n);
+                return;
+            }
+            "rm" => {
+                let i18n = I18n::with_lang(lang_override.as_deref());
+                run_rm(&args[i + 1..], &i18n);
+                return;
+            }
+            "connect" => {
+                let i18n = I18n::with_lang(lang_override.as_deref());
+                std::process::exit(run_connect(&args[i + 1..], &i18n));
+            }
+            "exec" => {
+                let i18n = I18n::with_lang(lang_override.as_deref());
+                std::process::exit(run_exec(&args[i + 1..], &i18n));
+            }
             _ => {
                 let i18n = I18n::with_lang(lang_override.as_deref());
                 eprintln!("{}", i18n.unknown_arg(&args[i]));
@@ -47,32 +116,166 @@ fn main() {
     let i18n = I18n::with_lang(lang_override.as_deref());
     ensure_sshpass(&i18n);
 
-    let mut cfg = Config::load();
+    let Some(mut cfg) = load_config_interactive(&i18n) else {
+        std::process::exit(1);
+    };
+    let unlock_warning = unlock_config(&mut cfg, &i18n);
 
-    loop {
+    // Background tunnels outlive each TUI session and are killed on quit.
+    let mut tunnels = tunnel::TunnelManager::new();
+    let health = health::HealthChecker::start(&cfg.hosts, &cfg.health_check);
+    let mut history = History::load(&History::path());
+    let mut session = ui::Session { status: unlock_warning, ..Default::default() };
+    let mut quit = false;
+    while !quit {
         let hosts = cfg.hosts.clone();
         let i18n_clone = I18n::with_lang(lang_override.as_deref());
-        ui::Ui::run(hosts, i18n_clone, |action| match action {
+        let mut hosts_changed = false;
+        let mut status = None;
+        ui::Ui::run(hosts, i18n_clone, &mut tunnels, &health, history.stats(), &mut session, |action| match action {
             ui::Action::Connect(h) => {
-                ssh_connect(&h, &i18n);
+                match cfg.resolve_jump(&h) {
+                    Ok(resolved) => {
+                        let started = (history::now(), Instant::now());
+                        let exit_code = ssh_connect(&resolved, &cfg.multiplex, &i18n);
+                        record_session(&mut history, &h.name, started, exit_code, &i18n);
+                    }
+                    Err(e) => {
+                        eprintln!("{}", i18n.jump_error(&e.to_string()));
+                        println!("{}", i18n.press_enter_to_return());
+                        wait_for_keypress(&i18n);
+                    }
+                }
+            }
+            ui::Action::Tunnel(h) => {
+                match cfg.resolve_jump(&h) {
+                    Ok(resolved) => run_tunnel(&resolved, &i18n),
+                    Err(e) => {
+                        eprintln!("{}", i18n.jump_error(&e.to_string()));
+                        println!("{}", i18n.press_enter_to_return());
+                        wait_for_keypress(&i18n);
+                    }
+                }
+            }
+            ui::Action::Transfer(h, transfer) => {
+                match cfg.resolve_jump(&h) {
+                    Ok(resolved) => run_transfer(&resolved, &transfer, &i18n),
+                    Err(e) => {
+                        eprintln!("{}", i18n.jump_error(&e.to_string()));
+                        println!("{}", i18n.press_enter_to_return());
+                        wait_for_keypress(&i18n);
+                    }
+                }
+            }
+            ui::Action::Browse(h) => {
+                match cfg.resolve_jump(&h) {
+                    Ok(resolved) => run_browser(&resolved, &i18n),
+                    Err(e) => {
+                        eprintln!("{}", i18n.jump_error(&e.to_string()));
+                        println!("{}", i18n.press_enter_to_return());
+                        wait_for_keypress(&i18n);
+                    }
+                }
+            }
+            ui::Action::CheckMaster(h) => {
+                match multiplex::control(&h, "check") {
+                    Ok(reply) => println!("{}", i18n.master_running(&h.name, &reply)),
+                    Err(e) => eprintln!("{}", i18n.master_not_running(&h.name, &e)),
+                }
+                println!("{}", i18n.press_enter_to_return());
+                wait_for_keypress(&i18n);
+            }
+            ui::Action::CloseMaster(h) => {
+                match multiplex::control(&h, "exit") {
+                    Ok(_) => println!("{}", i18n.master_closed(&h.name)),
+                    Err(e) => eprintln!("{}", i18n.master_close_failed(&h.name, &e)),
+                }
+                println!("{}", i18n.press_enter_to_return());
+                wait_for_keypress(&i18n);
+            }
+            ui::Action::Restore(path) => {
+                match backup::restore(&path, &Config::path(), &backup::backups_dir(), cfg.backups.keep) {
+                    Ok(()) => match Config::load() {
+                        Ok(restored) => {
+                            cfg = restored;
+                            unlock_config(&mut cfg, &i18n);
+                            hosts_changed = true;
+                            println!("{}", i18n.config_restored(&path.display().to_string()));
+                        }
+                        Err(e) => eprintln!("{}", i18n.config_load_error(&Config::path().display().to_string(), &e.to_string())),
+                    },
+                    Err(e) => eprintln!("{}", i18n.config_restore_failed(&e)),
+                }
+                println!("{}", i18n.press_enter_to_return());
+                wait_for_keypress(&i18n);
             }
             ui::Action::Add(h) => {
+                let before = cfg.clone();
                 cfg.add_host(h);
-                cfg.save();
+                status = save_from_tui(&mut cfg, before, &i18n);
+                hosts_changed = true;
             }
             ui::Action::Edit(idx, h) => {
                 if idx < cfg.hosts.len() {
+                    let before = cfg.clone();
                     cfg.update_host(idx, h);
-                    cfg.save();
+                    status = save_from_tui(&mut cfg, before, &i18n);
+                    hosts_changed = true;
+                }
+            }
+            ui::Action::Delete(mut indices) => {
+                let before = cfg.clone();
+                // Highest first, so earlier removals don't shift later ones.
+                indices.sort_unstable_by(|a, b| b.cmp(a));
+                for idx in indices {
+                    cfg.remove_host(idx);
                 }
+                status = save_from_tui(&mut cfg, before, &i18n);
+                hosts_changed = true;
             }
-            ui::Action::Delete(idx) => {
-                cfg.remove_host(idx);
-                cfg.save();
+            ui::Action::Import => {
+                import_ssh_config(&mut cfg, &ssh_config::default_path(), &i18n);
+                hosts_changed = true;
+                println!("{}", i18n.press_enter_to_return());
+                wait_for_keypress(&i18n);
+            }
+            ui::Action::Exec(targets) => {
+                let command: String = dialoguer::Input::new()
+                    .with_prompt(i18n.exec_command_prompt())
+                    .allow_empty(true)
+                    .interact_text()
+                    .unwrap_or_default();
+                if !command.trim().is_empty() {
+                    exec_on_hosts(&targets, &cfg.hosts, &command, exec::DEFAULT_JOBS, false, &i18n);
+                }
+                println!("{}", i18n.press_enter_to_return());
+                wait_for_keypress(&i18n);
             }
             ui::Action::Copy => {}
-            ui::Action::Quit => std::process::exit(0),
+            ui::Action::Quit => quit = true,
         }).ok();
+        session.status = status;
+        if hosts_changed {
+            health.set_hosts(&cfg.hosts);
+        }
+    }
+    tunnels.stop_all();
+}
+
+/// Saves a change made in the TUI. When it can't be saved the change is
+/// undone, both the hosts and the loaded document, so the list keeps
+/// matching the file, and the returned message is shown in the TUI.
+fn save_from_tui(cfg: &mut Config, before: Config, i18n: &I18n) -> Option<String> {
+    let e = cfg.save().err()?;
+    *cfg = before;
+    Some(i18n.config_save_error(&e.to_string()))
+}
+
+/// Saves a change made from the command line, exiting when it can't.
+fn save_config(cfg: &mut Config, i18n: &I18n) {
+    if let Err(e) = cfg.save() {
+        eprintln!("{}", i18n.config_save_error(&e.to_string()));
+        std::process::exit(1);
     }
 }
 
@@ -86,6 +289,29 @@ fn print_help(i18n: &I18n) {
     println!("  -h, --help       {}", i18n.help_help());
     println!("  -l, --lang CODE  {}", i18n.help_lang());
     println!();
+    println!("{}", i18n.help_commands());
+    println!("  import --ssh-config [PATH]  {}", i18n.help_import());
+    println!("  export --ssh-config [PATH]  {}", i18n.help_export());
+    println!("  vault init                  {}", i18n.help_vault_init());
+    println!("  vault change-passphrase     {}", i18n.help_vault_change());
+    println!("  config restore [N|FILE] [--yes]");
+    println!("                              {}", i18n.help_config_restore());
+    println!("  list [--format table|tsv|json] [--show-secrets]");
+    println!("                              {}", i18n.help_list());
+    println!("  add [HOST OPTIONS]          {}", i18n.help_add());
+    println!("  edit NAME [HOST OPTIONS]    {}", i18n.help_edit());
+    println!("  rm NAME                     {}", i18n.help_rm());
+    println!("  connect NAME                {}", i18n.help_connect());
+    println!("  exec PATTERN [-j N] [--collect] -- COMMAND");
+    println!("                              {}", i18n.help_exec());
+    println!();
+    println!("{}", i18n.help_host_options());
+    println!("  --name NAME  --user USER  --host HOST  --port PORT");
+    println!("  --password PASSWORD | --password-stdin");
+    println!("  --identity PATH  --identities-only");
+    println!("  --jump HOSTS  --control-persist TIME  --group PATH  --tags TAG,TAG");
+    println!("  --forward \"L 5432:db:5432\" (repeatable)  --command CMD");
+    println!();
     println!("{}", i18n.help_no_args());
     println!();
     if i18n.is_chinese {
@@ -95,64 +321,777 @@ fn print_help(i18n: &I18n) {
     }
 }
 
-fn ensure_sshpass(i18n: &I18n) {
-    if which("sshpass").is_ok() { return; }
-    println!("{}", i18n.sshpass_not_found());
+fn run_import(args: &[String], i18n: &I18n) {
+    let mut path: Option<PathBuf> = None;
+    let mut i = 0;
+    while i < args.len() {
+        match args[i].as_str() {
+            "--ssh-config" => {
+                path = Some(match args.get(i + 1) {
+                    Some(p) if !p.starts_with('-') => {
+                        i += 1;
+                        PathBuf::from(p)
+                    }
+                    _ => ssh_config::default_path(),
+                });
+            }
+            other => {
+                eprintln!("{}", i18n.unknown_arg(other));
+                eprintln!("{}", i18n.use_help());
+                std::process::exit(1);
+            }
+        }
+        i += 1;
+    }
+
+    let Some(path) = path else {
+        eprintln!("{}", i18n.import_requires_source());
+        std::process::exit(1);
+    };
+
+    let mut cfg = load_config(i18n);
+    if !import_ssh_config(&mut cfg, &path, i18n) {
+        std::process::exit(1);
+    }
+}
+
+fn import_ssh_config(cfg: &mut Config, path: &Path, i18n: &I18n) -> bool {
+    match ssh_config::load(path) {
+        Ok(hosts) => {
+            let before = cfg.clone();
+            let report = cfg.import_hosts(hosts);
+            if !report.added.is_empty() {
+                if let Err(e) = cfg.save() {
+                    *cfg = before;
+                    eprintln!("{}", i18n.config_save_error(&e.to_string()));
+                    return false;
+                }
+            }
+            println!("{}", i18n.import_summary(report.added.len(), report.duplicates.len()));
+            for name in &report.duplicates {
+                println!("{}", i18n.import_duplicate(name));
+            }
+            true
+        }
+        Err(e) => {
+            eprintln!("{}", i18n.import_read_error(&path.display().to_string(), &e.to_string()));
+            false
+        }
+    }
 }
 
+fn run_export(args: &[String], i18n: &I18n) {
+    let mut path: Option<PathBuf> = None;
+    let mut i = 0;
+    while i < args.len() {
+        match args[i].as_str() {
+            "--ssh-config" => {
+                path = Some(match args.get(i + 1) {
+                    Some(p) if !p.starts_with('-') => {
+                        i += 1;
+                        PathBuf::from(p)
+                    }
+                    _ => Config::ssh_config_path(),
+                });
+            }
+            other => {
+                eprintln!("{}", i18n.unknown_arg(other));
+                eprintln!("{}", i18n.use_help());
+                std::process::exit(1);
+            }
+        }
+        i += 1;
+    }
 
-fn ssh_connect(h: &Host, i18n: &I18n) {
-    let mut ssh_args = vec!["-t".to_string()];
-    
-    ssh_args.push("-o".to_string());
-    ssh_args.push("ConnectTimeout=30".to_string());
-    
-    ssh_args.push("-o".to_string());
-    ssh_args.push("StrictHostKeyChecking=accept-new".to_string());
-    
-    if let Some(port) = h.port {
-        ssh_args.push("-p".to_string());
-        ssh_args.push(port.to_string());
+    let Some(path) = path else {
+        eprintln!("{}", i18n.export_requires_target());
+        std::process::exit(1);
+    };
+
+    let cfg = load_config(i18n);
+    // ssh doesn't create the directory of a ControlPath.
+    if cfg.hosts.iter().any(|h| cfg.multiplex.persist_for(h).is_some()) {
+        let _ = multiplex::create_sockets_dir();
+    }
+    if let Err(e) = std::fs::write(&path, cfg.to_ssh_config()) {
+        eprintln!("{}", i18n.export_write_error(&path.display().to_string(), &e.to_string()));
+        std::process::exit(1);
     }
+    println!("{}", i18n.export_summary(cfg.hosts.len(), &path.display().to_string()));
+    println!("{}", i18n.export_include_hint(&path.display().to_string()));
+}
 
-    let userhost = format!("{}@{}", h.user, h.host);
+fn run_vault(args: &[String], i18n: &I18n) {
+    let mut cfg = load_config(i18n);
+    match args.first().map(String::as_str) {
+        Some("init") => {
+            if cfg.vault.is_some() {
+                eprintln!("{}", i18n.vault_already_exists());
+                std::process::exit(1);
+            }
+            let params = parse_kdf_params(&args[1..], KdfParams::default(), i18n);
+            let passphrase = prompt_new_passphrase(i18n);
+            let migrated = cfg.plaintext_password_count();
+            if let Err(e) = cfg.set_passphrase(&passphrase, params) {
+                eprintln!("{}", i18n.vault_error(&e.to_string()));
+                std::process::exit(1);
+            }
+            save_config(&mut cfg, i18n);
+            println!("{}", i18n.vault_created(migrated));
+        }
+        Some("change-passphrase") => {
+            let Some(current) = cfg.vault.as_ref().map(|v| v.params().at_least_minimum()) else {
+                eprintln!("{}", i18n.vault_not_initialized());
+                std::process::exit(1);
+            };
+            let params = parse_kdf_params(&args[1..], current, i18n);
+            unlock_config(&mut cfg, i18n);
+            let passphrase = prompt_new_passphrase(i18n);
+            if let Err(e) = cfg.set_passphrase(&passphrase, params) {
+                eprintln!("{}", i18n.vault_error(&e.to_string()));
+                std::process::exit(1);
+            }
+            save_config(&mut cfg, i18n);
+            println!("{}", i18n.vault_passphrase_changed());
+        }
+        Some(other) => {
+            eprintln!("{}", i18n.unknown_arg(other));
+            eprintln!("{}", i18n.use_help());
+            std::process::exit(1);
+        }
+        None => {
+            eprintln!("{}", i18n.vault_requires_subcommand());
+            std::process::exit(1);
+        }
+    }
+}
 
-    if let Some(cmd) = &h.command {
-        let commands: Vec<&str> = cmd.lines()
-            .map(|l| l.trim())
-            .filter(|l| !l.is_empty())
-            .collect();
-        
-        if !commands.is_empty() {
-            let combined_cmd = commands.join("; ");
-            ssh_args.push(userhost);
-            ssh_args.push(format!("{}; exec $SHELL -l", combined_cmd));
-            return build_ssh_command(h, ssh_args, i18n);
+fn run_list(args: &[String], i18n: &I18n) {
+    let mut format = ListFormat::Table;
+    let mut show_secrets = false;
+    let mut i = 0;
+    while i < args.len() {
+        match args[i].as_str() {
+            "--format" => {
+                let Some(value) = args.get(i + 1) else {
+                    eprintln!("{}", i18n.cli_requires_value("--format"));
+                    std::process::exit(1);
+                };
+                format = ListFormat::parse(value).unwrap_or_else(|| {
+                    eprintln!("{}", i18n.cli_invalid_value("--format", value));
+                    std::process::exit(1);
+                });
+                i += 1;
+            }
+            "--show-secrets" => show_secrets = true,
+            other => {
+                eprintln!("{}", i18n.unknown_arg(other));
+                eprintln!("{}", i18n.use_help());
+                std::process::exit(1);
+            }
         }
+        i += 1;
     }
-    
-    ssh_args.push(userhost);
-    build_ssh_command(h, ssh_args, i18n)
+
+    let mut cfg = load_config(i18n);
+    if show_secrets {
+        unlock_config(&mut cfg, i18n);
+    }
+    if cfg.hosts.is_empty() && format == ListFormat::Table {
+        eprintln!("{}", i18n.cli_no_hosts());
+        return;
+    }
+    print!("{}", listing::render(&cfg.hosts, format, show_secrets));
 }
 
-fn build_ssh_command(h: &Host, ssh_args: Vec<String>, i18n: &I18n) {
-    use std::process::Stdio;
+fn run_add(args: &[String], i18n: &I18n) {
+    let mut cfg = load_config(i18n);
+    let mut host = Host { port: Some(22), ..Default::default() };
+    let set_password = apply_host_args(&mut host, args, i18n);
 
-    let mut cmd = if let Some(pw) = &h.password {
-        if which("sshpass").is_err() {
-            eprintln!("{}", i18n.sshpass_cannot_login());
-            wait_for_keypress(i18n);
+    let missing: Vec<&str> = [("--name", &host.name), ("--user", &host.user), ("--host", &host.host)]
+        .iter()
+        .filter(|(_, value)| value.is_empty())
+        .map(|(flag, _)| *flag)
+        .collect();
+    if !missing.is_empty() {
+        eprintln!("{}", i18n.cli_missing_fields(&missing.join(", ")));
+        std::process::exit(1);
+    }
+    if cfg.host_index(&host.name).is_some() {
+        eprintln!("{}", i18n.cli_host_exists(&host.name));
+        std::process::exit(1);
+    }
+
+    if set_password {
+        unlock_config(&mut cfg, i18n);
+    }
+    let name = host.name.clone();
+    cfg.add_host(host);
+    save_config(&mut cfg, i18n);
+    println!("{}", i18n.cli_host_added(&name));
+}
+
+fn run_edit(args: &[String], i18n: &I18n) {
+    let mut cfg = load_config(i18n);
+    let idx = named_host_index(&cfg, args, "edit", i18n);
+    let mut host = cfg.hosts[idx].clone();
+    let set_password = apply_host_args(&mut host, &args[1..], i18n);
+
+    if host.name.is_empty() || host.user.is_empty() || host.host.is_empty() {
+        eprintln!("{}", i18n.cli_missing_fields("--name, --user, --host"));
+        std::process::exit(1);
+    }
+    if cfg.host_index(&host.name).is_some_and(|other| other != idx) {
+        eprintln!("{}", i18n.cli_host_exists(&host.name));
+        std::process::exit(1);
+    }
+
+    if set_password {
+        unlock_config(&mut cfg, i18n);
+    }
+    let name = host.name.clone();
+    cfg.update_host(idx, host);
+    save_config(&mut cfg, i18n);
+    println!("{}", i18n.cli_host_updated(&name));
+}
+
+fn run_rm(args: &[String], i18n: &I18n) {
+    let mut cfg = load_config(i18n);
+    let idx = named_host_index(&cfg, args, "rm", i18n);
+    if let Some(extra) = args.get(1) {
+        eprintln!("{}", i18n.unknown_arg(extra));
+        std::process::exit(1);
+    }
+    let name = cfg.hosts[idx].name.clone();
+    cfg.remove_host(idx);
+    save_config(&mut cfg, i18n);
+    println!("{}", i18n.cli_host_removed(&name));
+}
+
+/// `lazyssh config restore [N|FILE] [--yes]`. Works even when the current
+/// config doesn't load, since that is when a backup is most needed.
+fn run_config(args: &[String], i18n: &I18n) {
+    if args.first().map(String::as_str) != Some("restore") {
+        match args.first() {
+            Some(other) => eprintln!("{}", i18n.unknown_arg(other)),
+            None => eprintln!("{}", i18n.config_requires_subcommand()),
+        }
+        std::process::exit(1);
+    }
+    let mut yes = false;
+    let mut target: Option<&str> = None;
+    for arg in &args[1..] {
+        match arg.as_str() {
+            "-y" | "--yes" => yes = true,
+            other if target.is_none() && !other.starts_with('-') => target = Some(other),
+            other => {
+                eprintln!("{}", i18n.unknown_arg(other));
+                std::process::exit(1);
+            }
+        }
+    }
+
+    let dir = backup::backups_dir();
+    let backups = backup::list(&dir).unwrap_or_default();
+    let Some(target) = target else {
+        if backups.is_empty() {
+            println!("{}", i18n.config_no_backups(&dir.display().to_string()));
+            return;
+        }
+        for (idx, b) in backups.iter().enumerate() {
+            println!("{:>3}  {} UTC  {}", idx + 1, b.created, b.name());
+        }
+        println!("\n{}", i18n.config_restore_hint());
+        return;
+    };
+    let path = match target.parse::<usize>() {
+        Ok(n) => match backups.get(n.wrapping_sub(1)) {
+            Some(b) => b.path.clone(),
+            None => {
+                eprintln!("{}", i18n.config_no_such_backup(target));
+                std::process::exit(1);
+            }
+        },
+        Err(_) if Path::new(target).is_file() => PathBuf::from(target),
+        Err(_) if dir.join(target).is_file() => dir.join(target),
+        Err(_) => {
+            eprintln!("{}", i18n.config_no_such_backup(target));
+            std::process::exit(1);
+        }
+    };
+
+    let current = std::fs::read_to_string(Config::path()).unwrap_or_default();
+    let restored = std::fs::read_to_string(&path).unwrap_or_default();
+    let changes = backup::diff(&current, &restored, 2);
+    if changes.iter().all(|line| matches!(line, backup::DiffLine::Skipped(_))) {
+        println!("{}", i18n.config_backup_unchanged());
+        return;
+    }
+    for line in &changes {
+        match line {
+            backup::DiffLine::Same(text) => println!("  {}", text),
+            backup::DiffLine::Removed(text) => println!("\x1b[31m- {}\x1b[0m", text),
+            backup::DiffLine::Added(text) => println!("\x1b[32m+ {}\x1b[0m", text),
+            backup::DiffLine::Skipped(n) => println!("\x1b[2m  ⋯ {}\x1b[0m", i18n.config_unchanged_lines(*n)),
+        }
+    }
+    println!();
+    if !yes {
+        let confirmed = dialoguer::Confirm::new()
+            .with_prompt(i18n.config_restore_confirm())
+            .default(false)
+            .interact()
+            .unwrap_or(false);
+        if !confirmed {
             return;
         }
-        let mut sshpass_cmd = Command::new("sshpass");
-        sshpass_cmd.arg("-p").arg(pw).arg("ssh");
-        sshpass_cmd.args(&ssh_args);
-        sshpass_cmd
+    }
+    // The current config may be the broken one being replaced.
+    let keep = Config::load().map(|cfg| cfg.backups.keep).unwrap_or_default();
+    match backup::restore(&path, &Config::path(), &dir, keep) {
+        Ok(()) => println!("{}", i18n.config_restored(&path.display().to_string())),
+        Err(e) => {
+            eprintln!("{}", i18n.config_restore_failed(&e));
+            std::process::exit(1);
+        }
+    }
+}
+
+/// Returns ssh's exit code, or 1 when ssh could not be started.
+fn run_connect(args: &[String], i18n: &I18n) -> i32 {
+    let mut cfg = load_config(i18n);
+    let idx = named_host_index(&cfg, args, "connect", i18n);
+    if let Some(extra) = args.get(1) {
+        eprintln!("{}", i18n.unknown_arg(extra));
+        return 1;
+    }
+    unlock_config(&mut cfg, i18n);
+    let resolved = match cfg.resolve_jump(&cfg.hosts[idx]) {
+        Ok(resolved) => resolved,
+        Err(e) => {
+            eprintln!("{}", i18n.jump_error(&e.to_string()));
+            return 1;
+        }
+    };
+    let started = (history::now(), Instant::now());
+    let mut ssh_args = multiplex::connect_args(&resolved, &cfg.multiplex);
+    ssh_args.extend(ssh::connect_args(&resolved));
+    let exit_code = build_ssh_command("ssh", &resolved, ssh_args, i18n);
+    record_session(&mut History::load(&History::path()), &resolved.name, started, exit_code, i18n);
+    exit_code.unwrap_or(1)
+}
+
+/// Appends a finished ssh session to the connection history. `started` is
+/// the wall-clock start time and the instant it was taken at.
+fn record_session(history: &mut History, host: &str, started: (u64, Instant), exit_code: Option<i32>, i18n: &I18n) {
+    let record = history::Record {
+        host: host.to_string(),
+        timestamp: started.0,
+        duration: started.1.elapsed().as_secs(),
+        exit_code,
+    };
+    if let Err(e) = history.append(&History::path(), record) {
+        eprintln!("{}", i18n.history_write_error(&e.to_string()));
+    }
+}
+
+/// Returns 0 when the command succeeded on every host, 1 otherwise.
+fn run_exec(args: &[String], i18n: &I18n) -> i32 {
+    let (options, command) = match args.iter().position(|a| a == "--") {
+        Some(pos) => (&args[..pos], args[pos + 1..].join(" ")),
+        None => (args, String::new()),
+    };
+
+    let mut pattern: Option<&str> = None;
+    let mut jobs = exec::DEFAULT_JOBS;
+    let mut collect = false;
+    let mut i = 0;
+    while i < options.len() {
+        match options[i].as_str() {
+            "-j" | "--jobs" => {
+                let value = options.get(i + 1).map(String::as_str).unwrap_or_default();
+                jobs = match value.parse::<usize>() {
+                    Ok(n) if n > 0 => n,
+                    _ => {
+                        eprintln!("{}", i18n.cli_invalid_value("--jobs", value));
+                        return 1;
+                    }
+                };
+                i += 1;
+            }
+            "--collect" => collect = true,
+            other if pattern.is_none() && !other.starts_with('-') => pattern = Some(other),
+            other => {
+                eprintln!("{}", i18n.unknown_arg(other));
+                eprintln!("{}", i18n.use_help());
+                return 1;
+            }
+        }
+        i += 1;
+    }
+
+    let Some(pattern) = pattern else {
+        eprintln!("{}", i18n.exec_requires_pattern());
+        return 1;
+    };
+    if command.trim().is_empty() {
+        eprintln!("{}", i18n.exec_requires_command());
+        return 1;
+    }
+
+    let mut cfg = load_config(i18n);
+    let targets: Vec<Host> = exec::select_hosts(&cfg.hosts, pattern).into_iter().cloned().collect();
+    if targets.is_empty() {
+        eprintln!("{}", i18n.exec_no_match(pattern));
+        return 1;
+    }
+    if targets.iter().any(|h| h.password.is_some()) {
+        unlock_config(&mut cfg, i18n);
+    }
+    // Pick up decrypted passwords.
+    let targets: Vec<Host> = targets.iter()
+        .filter_map(|t| cfg.host_index(&t.name).map(|idx| cfg.hosts[idx].clone()))
+        .collect();
+
+    if exec_on_hosts(&targets, &cfg.hosts, &command, jobs, collect, i18n) { 0 } else { 1 }
+}
+
+/// Runs `command` on `targets`, printing output and a summary table.
+/// Output is prefixed with the host name as it arrives, or printed in one
+/// block per host when `collect` is set. Returns whether every host
+/// succeeded.
+fn exec_on_hosts(targets: &[Host], hosts: &[Host], command: &str, jobs: usize, collect: bool, i18n: &I18n) -> bool {
+    println!("{}", i18n.exec_running(command, targets.len(), jobs.min(targets.len())));
+    let width = targets.iter().map(|h| h.name.chars().count()).max().unwrap_or(0);
+    let results = exec::run(targets, hosts, command, jobs, |name, line| {
+        if !collect {
+            println!("[{:width$}] {}", name, line, width = width);
+        }
+    });
+
+    if collect {
+        for result in &results {
+            println!("── {} ──", result.name);
+            for line in &result.output {
+                println!("{}", line);
+            }
+            println!();
+        }
+    }
+
+    println!();
+    print!("{}", exec::summary_table(&results));
+    let ok = results.iter().filter(|r| r.success()).count();
+    println!("{}", i18n.exec_summary(ok, results.len()));
+    ok == results.len()
+}
+
+/// Looks up the host named by the first argument, exiting when it's
+/// missing or unknown.
+fn named_host_index(cfg: &Config, args: &[String], command: &str, i18n: &I18n) -> usize {
+    let Some(name) = args.first().filter(|a| !a.starts_with("--")) else {
+        eprintln!("{}", i18n.cli_requires_host_name(command));
+        std::process::exit(1);
+    };
+    cfg.host_index(name).unwrap_or_else(|| {
+        eprintln!("{}", i18n.cli_host_not_found(name));
+        std::process::exit(1);
+    })
+}
+
+/// Applies `--name`, `--user`, ... flags onto `host`. Empty values clear
+/// optional fields. Returns whether a password was given, so the caller
+/// can unlock the vault to encrypt it.
+fn apply_host_args(host: &mut Host, args: &[String], i18n: &I18n) -> bool {
+    let mut set_password = false;
+    let mut forwards_given = false;
+    let mut i = 0;
+    while i < args.len() {
+        let flag = args[i].as_str();
+        match flag {
+            "--identities-only" => {
+                host.identities_only = true;
+                i += 1;
+                continue;
+            }
+            "--password-stdin" => {
+                let mut line = String::new();
+                if std::io::stdin().read_line(&mut line).is_err() {
+                    eprintln!("{}", i18n.cli_requires_value(flag));
+                    std::process::exit(1);
+                }
+                let password = line.trim_end_matches(['\r', '\n']).to_string();
+                host.password = Some(password).filter(|p| !p.is_empty());
+                set_password = true;
+                i += 1;
+                continue;
+            }
+            _ => {}
+        }
+
+        let Some(value) = args.get(i + 1).cloned() else {
+            eprintln!("{}", i18n.cli_requires_value(flag));
+            std::process::exit(1);
+        };
+        let optional = Some(value.clone()).filter(|v| !v.is_empty());
+        match flag {
+            "--name" => host.name = value,
+            "--user" => host.user = value,
+            "--host" => host.host = value,
+            "--port" => match value.parse() {
+                Ok(port) => host.port = Some(port),
+                Err(_) => {
+                    eprintln!("{}", i18n.cli_invalid_value(flag, &value));
+                    std::process::exit(1);
+                }
+            },
+            "--password" => {
+                host.password = optional;
+                set_password = true;
+            }
+            "--identity" => host.identity_file = optional,
+            "--jump" => host.jump = optional,
+            "--control-persist" => host.control_persist = optional,
+            "--group" => {
+                host.group = optional;
+                host.group = Some(host.group_path().join("/")).filter(|g| !g.is_empty());
+            }
+            "--tags" => host.tags = config::parse_tags(&value),
+            "--command" => host.command = optional,
+            "--forward" => {
+                // The first --forward replaces the host's forwards.
+                if !forwards_given {
+                    host.forwards.clear();
+                    forwards_given = true;
+                }
+                if value.is_empty() {
+                    // `--forward ""` just clears them.
+                } else if let Some(forward) = config::Forward::parse(&value) {
+                    host.forwards.push(forward);
+                } else {
+                    eprintln!("{}", i18n.cli_invalid_value(flag, &value));
+                    std::process::exit(1);
+                }
+            }
+            other => {
+                eprintln!("{}", i18n.unknown_arg(other));
+                eprintln!("{}", i18n.use_help());
+                std::process::exit(1);
+            }
+        }
+        i += 2;
+    }
+    if set_password && host.password.as_deref().is_some_and(vault::is_encrypted) {
+        eprintln!("{}", i18n.password_reserved_prefix());
+        std::process::exit(1);
+    }
+    set_password
+}
+
+fn parse_kdf_params(args: &[String], mut params: KdfParams, i18n: &I18n) -> KdfParams {
+    let mut i = 0;
+    while i < args.len() {
+        let flag = args[i].as_str();
+        let (field, min) = match flag {
+            "--kdf-memory" => (&mut params.memory_kib, vault::MIN_KDF_MEMORY_KIB),
+            "--kdf-iterations" => (&mut params.iterations, vault::MIN_KDF_ITERATIONS),
+            other => {
+                eprintln!("{}", i18n.unknown_arg(other));
+                eprintln!("{}", i18n.use_help());
+                std::process::exit(1);
+            }
+        };
+        let Some(value) = args.get(i + 1) else {
+            eprintln!("{}", i18n.cli_requires_value(flag));
+            std::process::exit(1);
+        };
+        match value.parse::<u32>() {
+            Ok(v) if v >= min => *field = v,
+            _ => {
+                eprintln!("{}", i18n.vault_invalid_kdf_value(flag, value, min));
+                std::process::exit(1);
+            }
+        }
+        i += 2;
+    }
+    params
+}
+
+/// Loads the config for a CLI command, exiting when it has an error.
+fn load_config(i18n: &I18n) -> Config {
+    Config::load().unwrap_or_else(|e| {
+        eprintln!("{}", i18n.config_load_error(&Config::path().display().to_string(), &e.to_string()));
+        std::process::exit(1);
+    })
+}
+
+/// Loads the config for the TUI. On an error the TUI explains it and offers
+/// to open the file in an editor until it loads; `None` means the user quit.
+fn load_config_interactive(i18n: &I18n) -> Option<Config> {
+    loop {
+        let error = match Config::load() {
+            Ok(cfg) => return Some(cfg),
+            Err(e) => e,
+        };
+        let path = Config::path();
+        match ui::Ui::show_load_error(&path, &error) {
+            Ok(ui::LoadErrorChoice::Edit) => {
+                if let Err(e) = open_in_editor(&path, error.line) {
+                    eprintln!("{}", i18n.editor_error(&e.to_string()));
+                    println!("{}", i18n.press_enter_to_return());
+                    wait_for_keypress(i18n);
+                }
+            }
+            Ok(ui::LoadErrorChoice::Reload) => {}
+            Ok(ui::LoadErrorChoice::Quit) | Err(_) => {
+                eprintln!("{}", i18n.config_load_error(&path.display().to_string(), &error.to_string()));
+                return None;
+            }
+        }
+    }
+}
+
+/// Opens `path` in `$VISUAL` or `$EDITOR` (vi, or notepad on Windows) and
+/// waits for it to close. Editors known to take `+LINE` start at `line`.
+fn open_in_editor(path: &Path, line: Option<usize>) -> std::io::Result<()> {
+    let editor = env::var("VISUAL").or_else(|_| env::var("EDITOR")).ok()
+        .filter(|e| !e.trim().is_empty())
+        .unwrap_or_else(|| if cfg!(windows) { "notepad".to_string() } else { "vi".to_string() });
+    let mut words = editor.split_whitespace();
+    let program = words.next().unwrap_or("vi");
+    let mut cmd = std::process::Command::new(program);
+    cmd.args(words);
+    let name = Path::new(program).file_stem().and_then(|s| s.to_str()).unwrap_or_default();
+    if let (Some(line), true) = (line, matches!(name, "vi" | "vim" | "nvim" | "nano" | "emacs" | "micro" | "kak")) {
+        cmd.arg(format!("+{}", line));
+    }
+    let status = cmd.arg(path).status()?;
+    if status.success() {
+        Ok(())
     } else {
-        let mut ssh_cmd = Command::new("ssh");
-        ssh_cmd.args(&ssh_args);
-        ssh_cmd
+        Err(std::io::Error::other(format!("{} exited with {}", program, status)))
+    }
+}
+
+/// Asks for the passphrase until the vault unlocks. Returns a warning
+/// about passwords that couldn't be decrypted, for the TUI to show again.
+fn unlock_config(cfg: &mut Config, i18n: &I18n) -> Option<String> {
+    if !cfg.is_locked() {
+        return None;
+    }
+    if let Ok(passphrase) = env::var("LAZYSSH_VAULT_PASSPHRASE") {
+        match cfg.unlock(&passphrase) {
+            Ok(()) => return None,
+            Err(e @ VaultError::CorruptedPasswords(_)) => {
+                eprintln!("{}", i18n.vault_warning(&e.to_string()));
+                return Some(i18n.vault_warning(&e.to_string()));
+            }
+            Err(e) => {
+                eprintln!("{}", i18n.vault_error(&e.to_string()));
+                std::process::exit(1);
+            }
+        }
+    }
+    for _ in 0..3 {
+        let passphrase = dialoguer::Password::new()
+            .with_prompt(i18n.vault_passphrase_prompt())
+            .allow_empty_password(true)
+            .interact()
+            .unwrap_or_else(|_| std::process::exit(1));
+        match cfg.unlock(&passphrase) {
+            Ok(()) => return None,
+            Err(e @ VaultError::CorruptedPasswords(_)) => {
+                eprintln!("{}", i18n.vault_warning(&e.to_string()));
+                return Some(i18n.vault_warning(&e.to_string()));
+            }
+            Err(VaultError::WrongPassphrase) => eprintln!("{}", i18n.vault_wrong_passphrase()),
+            Err(e) => {
+                eprintln!("{}", i18n.vault_error(&e.to_string()));
+                std::process::exit(1);
+            }
+        }
+    }
+    std::process::exit(1);
+}
+
+fn prompt_new_passphrase(i18n: &I18n) -> String {
+    dialoguer::Password::new()
+        .with_prompt(i18n.vault_new_passphrase_prompt())
+        .with_confirmation(i18n.vault_confirm_passphrase_prompt(), i18n.vault_passphrase_mismatch())
+        .interact()
+        .unwrap_or_else(|_| std::process::exit(1))
+}
+
+fn run_askpass(args: &[String]) -> i32 {
+    let prompt = args.iter().skip(1).find(|a| *a != "--askpass").cloned().unwrap_or_default();
+    let secret = env::var(ssh::ASKPASS_PASSWORD_ENV).ok();
+    let target = env::var(ssh::ASKPASS_TARGET_ENV).unwrap_or_default();
+    let identity = env::var(ssh::ASKPASS_IDENTITY_ENV).ok();
+    match ssh::askpass_answer(&prompt, &target, identity.as_deref(), secret.as_deref()) {
+        Some(answer) => {
+            println!("{}", answer);
+            0
+        }
+        None => askpass_from_terminal(&prompt),
+    }
+}
+
+/// Puts a prompt lazyssh has no answer for (an OTP code, a jump host's
+/// password, a host key confirmation) to the user on the terminal, the way
+/// ssh would without askpass. Refuses when there is no terminal.
+fn askpass_from_terminal(prompt: &str) -> i32 {
+    let term = dialoguer::console::Term::stderr();
+    if !term.is_term() || term.write_str(prompt).is_err() {
+        return 1;
+    }
+    // ssh sets this to `none` for notices and `confirm` for yes/no questions.
+    let answer = match env::var("SSH_ASKPASS_PROMPT").as_deref() {
+        Ok("none") => return i32::from(term.write_line("").is_err()),
+        Ok("confirm") => term.read_line(),
+        _ => term.read_secure_line(),
     };
+    match answer {
+        Ok(answer) => {
+            println!("{}", answer);
+            0
+        }
+        Err(_) => 1,
+    }
+}
+
+fn ensure_sshpass(i18n: &I18n) {
+    if ssh::password_method().is_some() { return; }
+    println!("{}", i18n.sshpass_not_found());
+}
+
+
+/// Returns ssh's exit code, after waiting for Enter when it failed.
+fn ssh_connect(h: &Host, mux: &config::Multiplex, i18n: &I18n) -> Option<i32> {
+    let mut ssh_args = multiplex::connect_args(h, mux);
+    ssh_args.extend(ssh::connect_args(h));
+    let exit_code = build_ssh_command("ssh", h, ssh_args, i18n);
+    match exit_code {
+        Some(255) => {
+            eprintln!("{}", i18n.press_enter_to_return());
+            wait_for_keypress(i18n);
+        }
+        None => wait_for_keypress(i18n),
+        Some(_) => {}
+    }
+    exit_code
+}
+
+/// Runs ssh (or scp) in the foreground and returns its exit code. Failures
+/// are explained on stderr; `None` means it could not be run at all.
+fn build_ssh_command(program: &str, h: &Host, ssh_args: Vec<String>, i18n: &I18n) -> Option<i32> {
+    use std::process::Stdio;
+
+    if h.password.is_some() && ssh::password_method().is_none() {
+        eprintln!("{}", i18n.sshpass_cannot_login());
+        return None;
+    }
+    let mut cmd = ssh::command(program, h, &ssh_args);
 
     cmd.stdin(Stdio::inherit())
        .stdout(Stdio::inherit())
@@ -168,20 +1107,112 @@ fn build_ssh_command(h: &Host, ssh_args: Vec<String>, i18n: &I18n) {
                             eprintln!("{}", i18n.ssh_connection_failed(&userhost));
                             eprintln!("{}{}", i18n.exit_code(), exit_code);
                             eprintln!("{}", i18n.possible_reasons());
-                            eprintln!("{}", i18n.press_enter_to_return());
-                            wait_for_keypress(i18n);
                         }
                     }
+                    // No exit code means ssh was killed by a signal.
+                    Some(status.code().unwrap_or(128))
                 }
                 Err(e) => {
                     eprintln!("{}", i18n.wait_ssh_process_error(&e.to_string()));
-                    wait_for_keypress(i18n);
+                    None
                 }
             }
         }
+        Err(e) => {
+            eprintln!("{}", i18n.execute_ssh_error(&e.to_string()));
+            None
+        }
+    }
+}
+
+/// Copies files with scp in the foreground, so its progress meter shows,
+/// then reports the result until Enter is pressed.
+fn run_transfer(h: &Host, transfer: &ssh::Transfer, i18n: &I18n) {
+    let local = if transfer.local.is_empty() { "." } else { transfer.local.as_str() };
+    let remote = format!("{}:{}", h.name, transfer.remote);
+    match transfer.direction {
+        ssh::TransferDirection::Upload => println!("{}", i18n.transfer_upload(local, &remote)),
+        ssh::TransferDirection::Download => println!("{}", i18n.transfer_download(&remote, local)),
+    }
+
+    let started = Instant::now();
+    match build_ssh_command("scp", h, ssh::scp_args(h, transfer), i18n) {
+        Some(0) => println!("{}", i18n.transfer_done(started.elapsed().as_secs_f64())),
+        Some(code) => eprintln!("{}", i18n.transfer_failed(code)),
+        None => {}
+    }
+    println!("{}", i18n.press_enter_to_return());
+    wait_for_keypress(i18n);
+}
+
+/// Opens the dual-pane file browser on `h`, starting in the current
+/// directory. Connection errors are shown until Enter is pressed.
+fn run_browser(h: &Host, i18n: &I18n) {
+    println!("{}", i18n.browser_connecting(&h.name));
+    let local_dir = env::current_dir().unwrap_or_else(|_| PathBuf::from("."));
+    let result = sftp::Session::open(h)
+        .and_then(|session| browser::run(&session, &h.name, &local_dir, i18n).map_err(|e| e.to_string()));
+    if let Err(e) = result {
+        eprintln!("{}", i18n.browser_error(&e));
+        println!("{}", i18n.press_enter_to_return());
+        wait_for_keypress(i18n);
+    }
+}
+
+fn run_tunnel(h: &Host, i18n: &I18n) {
+    use std::process::Stdio;
+    use std::sync::mpsc;
+    use std::time::Duration;
+
+    if h.password.is_some() && ssh::password_method().is_none() {
+        eprintln!("{}", i18n.sshpass_cannot_login());
+        wait_for_keypress(i18n);
+        return;
+    }
+    let mut cmd = ssh::command("ssh", h, &ssh::tunnel_args(h));
+    cmd.stdin(Stdio::null());
+
+    let mut child = match cmd.spawn() {
+        Ok(child) => child,
         Err(e) => {
             eprintln!("{}", i18n.execute_ssh_error(&e.to_string()));
             wait_for_keypress(i18n);
+            return;
+        }
+    };
+
+    println!("{}", i18n.tunnel_open(&format!("{}@{}", h.user, h.host)));
+    for forward in &h.forwards {
+        println!("   {}", forward);
+    }
+    println!("{}", i18n.tunnel_press_enter_to_close());
+
+    let (tx, rx) = mpsc::channel();
+    std::thread::spawn(move || {
+        wait_for_keypress(&I18n::new());
+        let _ = tx.send(());
+    });
+
+    loop {
+        if rx.recv_timeout(Duration::from_millis(200)).is_ok() {
+            let _ = child.kill();
+            let _ = child.wait();
+            println!("{}", i18n.tunnel_closed());
+            return;
+        }
+        match child.try_wait() {
+            Ok(Some(status)) => {
+                eprintln!("{}", i18n.tunnel_exited(&status.code().map(|c| c.to_string()).unwrap_or_default()));
+                // The reader thread still owns the pending Enter press.
+                let _ = rx.recv();
+                return;
+            }
+            Ok(None) => {}
+            Err(e) => {
+                eprintln!("{}", i18n.wait_ssh_process_error(&e.to_string()));
+                let _ = rx.recv();
+                return;
+            }
         }
     }
 }
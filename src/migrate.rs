@@ -0,0 +1,55 @@
+use toml_edit::{value, DocumentMut, Item};
+
+/// The `version` written to config.toml. Bump it and add a step to `STEPS`
+/// when a change to the format needs existing files rewritten.
+pub const CURRENT: u32 = 1;
+
+/// `STEPS[n]` upgrades a version `n` document to version `n + 1`. Files
+/// written before there was a `version` key are version 0.
+const STEPS: &[fn(&mut DocumentMut)] = &[
+    // 0 → 1: everything added before versioning is optional, so only the
+    // version key is new.
+    |_| {},
+];
+
+fn version(doc: &DocumentMut) -> Option<u32> {
+    match doc.get("version") {
+        None => Some(0),
+        Some(item) => item.as_integer().and_then(|v| u32::try_from(v).ok()),
+    }
+}
+
+fn set_version(doc: &mut DocumentMut, version: u32) {
+    if let Some(existing) = doc.get_mut("version").and_then(Item::as_value_mut) {
+        let decor = existing.decor().clone();
+        *existing = (version as i64).into();
+        *existing.decor_mut() = decor;
+        return;
+    }
+    doc.insert("version", value(version as i64));
+    doc.sort_values_by(|a, _, b, _| (a.get() != "version").cmp(&(b.get() != "version")));
+    // Keeps comments at the top of the file apart from the new first line.
+    if let Some(first) = doc.iter_mut().find_map(|(_, item)| match item {
+        Item::Table(table) => Some(table),
+        Item::ArrayOfTables(tables) => tables.iter_mut().next(),
+        _ => None,
+    }) {
+        let prefix = first.decor().prefix().and_then(|p| p.as_str()).unwrap_or("").to_string();
+        if !prefix.starts_with('\n') {
+            first.decor_mut().set_prefix(format!("\n{}", prefix));
+        }
+    }
+}
+
+/// `source` upgraded to the current version one step at a time, or `None`
+/// when there is nothing to upgrade: it is current, from a newer lazyssh,
+/// or not valid TOML, which loading it reports.
+pub fn upgrade(source: &str) -> Option<String> {
+    let mut doc: DocumentMut = source.parse().ok()?;
+    let from = version(&doc).filter(|&v| v < CURRENT)?;
+    for (step, to) in STEPS.iter().zip(1..).skip(from as usize) {
+        step(&mut doc);
+        set_version(&mut doc, to);
+    }
+    Some(doc.to_string())
+}
@@ -0,0 +1,90 @@
+use crate::config::{Config, Host, Multiplex};
+use crate::ssh;
+use std::collections::HashSet;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+/// Leaves room under the ~104 byte limit on Unix socket paths for ssh's
+/// temporary suffix.
+const MAX_NAME_PREFIX: usize = 16;
+
+pub fn sockets_dir() -> PathBuf {
+    Config::path().with_file_name("sockets")
+}
+
+/// The control socket for a host entry. Names are shortened and may
+/// contain anything, so a hash of the full name keeps them apart.
+pub fn socket_path(h: &Host) -> PathBuf {
+    sockets_dir().join(socket_name(&h.name))
+}
+
+pub fn socket_name(name: &str) -> String {
+    let prefix: String = name.chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '-' || c == '_' { c } else { '_' })
+        .take(MAX_NAME_PREFIX)
+        .collect();
+    // FNV-1a, so names stay the same across builds.
+    let hash = name.bytes().fold(0xcbf29ce484222325u64, |acc, b| (acc ^ b as u64).wrapping_mul(0x100000001b3));
+    format!("{}-{:08x}", prefix, hash as u32)
+}
+
+/// Extra ssh options for connecting to `h`, empty when it isn't
+/// multiplexed. Windows' OpenSSH has no control master support.
+pub fn connect_args(h: &Host, settings: &Multiplex) -> Vec<String> {
+    match settings.persist_for(h) {
+        Some(persist) if cfg!(unix) && create_sockets_dir().is_ok() => ssh::multiplex_args(&socket_path(h), persist),
+        _ => vec![],
+    }
+}
+
+pub fn create_sockets_dir() -> io::Result<()> {
+    let mut builder = fs::DirBuilder::new();
+    builder.recursive(true);
+    #[cfg(unix)]
+    std::os::unix::fs::DirBuilderExt::mode(&mut builder, 0o700);
+    builder.create(sockets_dir())
+}
+
+/// Whether a master is listening on `socket`. A socket file left behind
+/// by a killed master refuses the connection.
+#[cfg(unix)]
+pub fn is_live(socket: &Path) -> bool {
+    std::os::unix::net::UnixStream::connect(socket).is_ok()
+}
+
+#[cfg(not(unix))]
+pub fn is_live(_socket: &Path) -> bool {
+    false
+}
+
+/// Names of the hosts with a live master.
+pub fn live_masters(hosts: &[Host]) -> HashSet<String> {
+    hosts.iter()
+        .filter(|h| is_live(&socket_path(h)))
+        .map(|h| h.name.clone())
+        .collect()
+}
+
+/// Runs `ssh -O command` against the master for `h` and returns ssh's
+/// reply, e.g. `Master running (pid=4242)`.
+pub fn control(h: &Host, command: &str) -> Result<String, String> {
+    let output = Command::new("ssh")
+        .args(ssh::control_args(h, &socket_path(h), command))
+        .stdin(Stdio::null())
+        .output()
+        .map_err(|e| e.to_string())?;
+    let reply = String::from_utf8_lossy(&output.stderr)
+        .lines()
+        .rfind(|line| !line.trim().is_empty())
+        .map(|line| line.trim().to_string())
+        .unwrap_or_default();
+    if output.status.success() {
+        Ok(reply)
+    } else if reply.is_empty() {
+        Err(format!("ssh exited with {}", output.status))
+    } else {
+        Err(reply)
+    }
+}
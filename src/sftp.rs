@@ -0,0 +1,298 @@
+use crate::config::Host;
+use crate::multiplex;
+use crate::ssh;
+use crate::tunnel::capture_last_line;
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Stdio};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// How long to wait for the master to authenticate, a little over ssh's
+/// own `ConnectTimeout`.
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(35);
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Entry {
+    pub name: String,
+    pub is_dir: bool,
+    /// Remote symlinks aren't resolved, so they may be directories too.
+    pub is_link: bool,
+    pub size: u64,
+}
+
+/// Lists a local directory, directories first.
+pub fn list_local(dir: &Path) -> io::Result<Vec<Entry>> {
+    let mut entries = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let path = entry.path();
+        entries.push(Entry {
+            name: entry.file_name().to_string_lossy().into_owned(),
+            is_dir: path.is_dir(),
+            is_link: entry.file_type().is_ok_and(|t| t.is_symlink()),
+            size: fs::metadata(&path).map(|m| m.len()).unwrap_or(0),
+        });
+    }
+    sort_entries(&mut entries);
+    Ok(entries)
+}
+
+/// Parses the output of sftp's `ls -la`, dropping `.` and `..`.
+pub fn parse_listing(output: &str) -> Vec<Entry> {
+    let mut entries: Vec<Entry> = output.lines()
+        .filter_map(parse_ls_line)
+        .filter(|e| e.name != "." && e.name != "..")
+        .collect();
+    sort_entries(&mut entries);
+    entries
+}
+
+/// `drwxr-xr-x    2 user group     4096 Jan  1 12:00 name with spaces`
+fn parse_ls_line(line: &str) -> Option<Entry> {
+    let mut rest = line;
+    let mut fields = Vec::with_capacity(8);
+    for _ in 0..8 {
+        rest = rest.trim_start();
+        let end = rest.find(char::is_whitespace)?;
+        fields.push(&rest[..end]);
+        rest = &rest[end..];
+    }
+    let name = rest.strip_prefix(' ')?;
+    let kind = fields[0].chars().next()?;
+    if name.is_empty() || !"-dlpscb".contains(kind) {
+        return None;
+    }
+    Some(Entry {
+        name: name.to_string(),
+        is_dir: kind == 'd',
+        is_link: kind == 'l',
+        size: fields[4].parse().unwrap_or(0),
+    })
+}
+
+fn sort_entries(entries: &mut [Entry]) {
+    entries.sort_by_key(|e| (!e.is_dir, e.name.to_lowercase()));
+}
+
+/// Quotes a path for an sftp batch file.
+pub fn quote(path: &str) -> String {
+    format!("\"{}\"", path.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+pub fn remote_join(dir: &str, name: &str) -> String {
+    if dir.ends_with('/') {
+        format!("{}{}", dir, name)
+    } else {
+        format!("{}/{}", dir, name)
+    }
+}
+
+pub fn remote_parent(dir: &str) -> String {
+    match dir.trim_end_matches('/').rsplit_once('/') {
+        Some(("", _)) | None => "/".to_string(),
+        Some((parent, _)) => parent.to_string(),
+    }
+}
+
+pub fn human_size(bytes: u64) -> String {
+    const UNITS: [&str; 5] = ["B", "K", "M", "G", "T"];
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{}B", bytes)
+    } else {
+        format!("{:.1}{}", size, UNITS[unit])
+    }
+}
+
+/// An sftp connection to one host. A control master keeps the connection
+/// (and any password or 2FA prompt) alive, and each operation runs a short
+/// `sftp -b` batch over it.
+pub struct Session {
+    host: Host,
+    socket: PathBuf,
+    master: Child,
+    /// The remote directory sftp starts in.
+    pub home: String,
+}
+
+impl Session {
+    /// Starts the master and waits until it has authenticated. `h` should
+    /// already have its jump hosts resolved.
+    pub fn open(h: &Host) -> Result<Self, String> {
+        if h.password.is_some() && ssh::password_method().is_none() {
+            return Err("no password helper (OpenSSH 8.4+ or sshpass)".into());
+        }
+        let socket = socket_path()?;
+        let _ = fs::remove_file(&socket);
+
+        let mut cmd = ssh::command("ssh", h, &ssh::master_args(h, &socket));
+        cmd.stdin(Stdio::null()).stdout(Stdio::null()).stderr(Stdio::piped());
+        let mut master = cmd.spawn().map_err(|e| e.to_string())?;
+        let last_error = Arc::new(Mutex::new(String::new()));
+        if let Some(stderr) = master.stderr.take() {
+            let last_error = Arc::clone(&last_error);
+            thread::spawn(move || capture_last_line(stderr, last_error));
+        }
+
+        // ssh only creates the socket once authentication has succeeded.
+        let started = Instant::now();
+        while !socket.exists() {
+            if let Ok(Some(status)) = master.try_wait() {
+                thread::sleep(Duration::from_millis(100));
+                let error = last_error.lock().map(|e| e.clone()).unwrap_or_default();
+                return Err(if error.is_empty() { format!("ssh exited with {}", status) } else { error });
+            }
+            if started.elapsed() > CONNECT_TIMEOUT {
+                let _ = master.kill();
+                let _ = master.wait();
+                return Err("timed out waiting for the connection".into());
+            }
+            thread::sleep(Duration::from_millis(50));
+        }
+
+        let mut session = Session { host: h.clone(), socket, master, home: String::new() };
+        if !session.is_own_master() {
+            return Err(format!("{} wasn't created by our ssh, not using it", session.socket.display()));
+        }
+        let output = session.run(&["pwd".to_string()])?;
+        session.home = output.lines()
+            .find_map(|line| line.strip_prefix("Remote working directory: "))
+            .unwrap_or("/")
+            .to_string();
+        Ok(session)
+    }
+
+    pub fn list(&self, dir: &str) -> Result<Vec<Entry>, String> {
+        self.run(&[format!("ls -la {}", quote(dir))]).map(|output| parse_listing(&output))
+    }
+
+    pub fn upload(&self, local: &Path, remote: &str) -> Result<(), String> {
+        self.run(&[format!("put -r {} {}", quote(&local.to_string_lossy()), quote(remote))]).map(drop)
+    }
+
+    pub fn download(&self, remote: &str, local: &Path) -> Result<(), String> {
+        self.run(&[format!("get -r {} {}", quote(remote), quote(&local.to_string_lossy()))]).map(drop)
+    }
+
+    pub fn rename(&self, from: &str, to: &str) -> Result<(), String> {
+        self.run(&[format!("rename {} {}", quote(from), quote(to))]).map(drop)
+    }
+
+    pub fn mkdir(&self, path: &str) -> Result<(), String> {
+        self.run(&[format!("mkdir {}", quote(path))]).map(drop)
+    }
+
+    /// Deletes a file, or a directory and everything in it. sftp's `rmdir`
+    /// only takes empty directories, so the tree is listed and removed
+    /// bottom-up in one batch.
+    pub fn remove(&self, path: &str, is_dir: bool) -> Result<(), String> {
+        let mut commands = Vec::new();
+        self.removal_commands(path, is_dir, &mut commands)?;
+        self.run(&commands).map(drop)
+    }
+
+    fn removal_commands(&self, path: &str, is_dir: bool, commands: &mut Vec<String>) -> Result<(), String> {
+        if !is_dir {
+            commands.push(format!("rm {}", quote(path)));
+            return Ok(());
+        }
+        for entry in self.list(path)? {
+            self.removal_commands(&remote_join(path, &entry.name), entry.is_dir, commands)?;
+        }
+        commands.push(format!("rmdir {}", quote(path)));
+        Ok(())
+    }
+
+    /// Whether the master answering on the socket is the one we started,
+    /// or ssh run by the sshpass we started.
+    fn is_own_master(&self) -> bool {
+        let Ok(output) = Command::new("ssh")
+            .args(ssh::control_args(&self.host, &self.socket, "check"))
+            .stdin(Stdio::null())
+            .output()
+        else {
+            return false;
+        };
+        let ours = self.master.id();
+        master_pid(&String::from_utf8_lossy(&output.stderr))
+            .is_some_and(|pid| pid == ours || parent_pid(pid) == Some(ours))
+    }
+
+    /// Runs one sftp batch and returns its output without the echoed
+    /// commands. The batch stops at the first failing command.
+    fn run(&self, commands: &[String]) -> Result<String, String> {
+        let mut child = Command::new("sftp")
+            .args(ssh::sftp_args(&self.host, &self.socket))
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| e.to_string())?;
+        if let Some(mut stdin) = child.stdin.take() {
+            let mut batch = commands.join("\n");
+            batch.push('\n');
+            stdin.write_all(batch.as_bytes()).map_err(|e| e.to_string())?;
+        }
+        let output = child.wait_with_output().map_err(|e| e.to_string())?;
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            return Err(stderr.lines()
+                .rfind(|line| !line.trim().is_empty())
+                .map(str::to_string)
+                .unwrap_or_else(|| format!("sftp exited with {}", output.status)));
+        }
+        Ok(String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .filter(|line| !line.starts_with("sftp>"))
+            .collect::<Vec<_>>()
+            .join("\n"))
+    }
+}
+
+impl Drop for Session {
+    fn drop(&mut self) {
+        let _ = self.master.kill();
+        let _ = self.master.wait();
+        let _ = fs::remove_file(&self.socket);
+    }
+}
+
+/// A socket path in the sockets directory, which only we can write to, so
+/// nobody else can bind a master there first.
+fn socket_path() -> Result<PathBuf, String> {
+    static NEXT: AtomicUsize = AtomicUsize::new(0);
+    let dir = multiplex::sockets_dir();
+    multiplex::create_sockets_dir().map_err(|e| e.to_string())?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mode = fs::metadata(&dir).map_err(|e| e.to_string())?.permissions().mode();
+        if mode & 0o077 != 0 {
+            return Err(format!("{} is open to other users; chmod 700 it", dir.display()));
+        }
+    }
+    Ok(dir.join(format!("sftp-{}-{}", std::process::id(), NEXT.fetch_add(1, Ordering::SeqCst))))
+}
+
+/// The pid in ssh's reply to `-O check`, e.g. `Master running (pid=4242)`.
+pub fn master_pid(reply: &str) -> Option<u32> {
+    let rest = &reply[reply.find("(pid=")? + "(pid=".len()..];
+    rest[..rest.find(')')?].parse().ok()
+}
+
+/// Linux only; elsewhere sshpass can't be told apart and the master must
+/// be our own child.
+fn parent_pid(pid: u32) -> Option<u32> {
+    let stat = fs::read_to_string(format!("/proc/{}/stat", pid)).ok()?;
+    // The command name in parentheses may contain spaces.
+    stat[stat.rfind(')')? + 1..].split_whitespace().nth(1)?.parse().ok()
+}
@@ -0,0 +1,360 @@
+use crate::config::Host;
+use crate::vault;
+use dirs::home_dir;
+use std::env;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::OnceLock;
+use which::which;
+
+pub const ASKPASS_ENV: &str = "LAZYSSH_ASKPASS";
+pub const ASKPASS_PASSWORD_ENV: &str = "LAZYSSH_ASKPASS_PASSWORD";
+pub const ASKPASS_TARGET_ENV: &str = "LAZYSSH_ASKPASS_TARGET";
+pub const ASKPASS_IDENTITY_ENV: &str = "LAZYSSH_ASKPASS_IDENTITY";
+
+/// How a stored password reaches ssh.
+pub enum PasswordMethod {
+    /// lazyssh itself, run by ssh through `SSH_ASKPASS`.
+    Askpass(PathBuf),
+    Sshpass,
+}
+
+/// Prefers the built-in askpass helper, which needs `SSH_ASKPASS_REQUIRE`
+/// (OpenSSH 8.4+), and falls back to `sshpass` when it is installed.
+pub fn password_method() -> Option<PasswordMethod> {
+    if supports_askpass_require() {
+        if let Ok(exe) = env::current_exe() {
+            return Some(PasswordMethod::Askpass(exe));
+        }
+    }
+    which("sshpass").ok().map(|_| PasswordMethod::Sshpass)
+}
+
+fn supports_askpass_require() -> bool {
+    static SUPPORTED: OnceLock<bool> = OnceLock::new();
+    *SUPPORTED.get_or_init(|| {
+        Command::new("ssh").arg("-V").output()
+            .ok()
+            .and_then(|out| parse_openssh_version(&String::from_utf8_lossy(&out.stderr)))
+            .is_some_and(|version| version >= (8, 4))
+    })
+}
+
+pub fn parse_openssh_version(banner: &str) -> Option<(u32, u32)> {
+    let rest = &banner[banner.find("OpenSSH_")? + "OpenSSH_".len()..];
+    let mut parts = rest.split(|c: char| !c.is_ascii_digit());
+    let major = parts.next()?.parse().ok()?;
+    let minor = parts.next()?.parse().ok()?;
+    Some((major, minor))
+}
+
+/// The `user@host` ssh names in the password prompts for `h`. ssh takes the
+/// user and host from ~/.ssh/config (`User`, `HostName`, `HostKeyAlias`), so
+/// they're resolved with `ssh -G`.
+pub fn prompt_target(h: &Host) -> String {
+    let resolved = Command::new("ssh")
+        .arg("-G")
+        .args(common_args(h))
+        .arg(format!("{}@{}", h.user, h.host))
+        .output()
+        .ok()
+        .filter(|out| out.status.success())
+        .map(|out| String::from_utf8_lossy(&out.stdout).into_owned())
+        .unwrap_or_default();
+    resolve_prompt_target(h, &resolved)
+}
+
+/// Picks the prompt's `user@host` out of `ssh -G` output, falling back to
+/// `h` itself for anything missing.
+pub fn resolve_prompt_target(h: &Host, ssh_g: &str) -> String {
+    let value = |key: &str| {
+        ssh_g.lines().find_map(|line| line.strip_prefix(key)?.strip_prefix(' ')).map(str::trim)
+    };
+    let user = value("user").unwrap_or(&h.user);
+    let host = value("hostkeyalias").or_else(|| value("hostname")).unwrap_or(&h.host);
+    format!("{}@{}", user, host.to_lowercase())
+}
+
+/// The identity file path as ssh shows it in its passphrase prompt.
+pub fn prompt_identity(h: &Host) -> Option<String> {
+    let identity = h.identity_file.as_ref()?;
+    match (identity.strip_prefix("~/"), home_dir()) {
+        (Some(rest), Some(home)) => Some(home.join(rest).to_string_lossy().into_owned()),
+        _ => Some(identity.clone()),
+    }
+}
+
+/// Answers an ssh askpass prompt with the stored secret: a password prompt
+/// naming `target` (`deploy@web's password:` or `(deploy@web) Password:`)
+/// or the passphrase prompt for the host's own `identity` file. Anything
+/// else, like a jump host's password or an OTP code, gets `None` and is left
+/// to the user so the secret never goes somewhere it doesn't belong.
+pub fn askpass_answer(prompt: &str, target: &str, identity: Option<&str>, secret: Option<&str>) -> Option<String> {
+    let prompt = prompt.trim_start();
+    let lower = prompt.to_lowercase();
+    let target = target.to_lowercase();
+    let names_target = lower.starts_with(&format!("{}'s ", target)) || lower.starts_with(&format!("({}) ", target));
+    let is_password = !target.is_empty() && names_target && lower.contains("password");
+    let is_passphrase = identity.is_some_and(|identity| {
+        prompt.strip_prefix("Enter passphrase for key '")
+            .is_some_and(|rest| rest.starts_with(&format!("{}'", identity)))
+    });
+    if is_password || is_passphrase {
+        secret.map(String::from)
+    } else {
+        None
+    }
+}
+
+pub fn connect_args(h: &Host) -> Vec<String> {
+    let mut ssh_args = vec!["-t".to_string()];
+    ssh_args.extend(common_args(h));
+    ssh_args.extend(forward_args(h));
+
+    ssh_args.push(format!("{}@{}", h.user, h.host));
+
+    if let Some(cmd) = &h.command {
+        let commands: Vec<&str> = cmd.lines()
+            .map(|l| l.trim())
+            .filter(|l| !l.is_empty())
+            .collect();
+
+        if !commands.is_empty() {
+            ssh_args.push(format!("{}; exec $SHELL -l", commands.join("; ")));
+        }
+    }
+
+    ssh_args
+}
+
+/// Arguments for running only the host's forwards, without a remote shell.
+/// Keepalives make a dropped link end the process so it shows up as exited.
+pub fn tunnel_args(h: &Host) -> Vec<String> {
+    let mut ssh_args = vec![
+        "-N".to_string(),
+        "-o".to_string(),
+        "ExitOnForwardFailure=yes".to_string(),
+        "-o".to_string(),
+        "ServerAliveInterval=15".to_string(),
+        "-o".to_string(),
+        "ServerAliveCountMax=3".to_string(),
+    ];
+    ssh_args.extend(common_args(h));
+    ssh_args.extend(forward_args(h));
+    ssh_args.push(format!("{}@{}", h.user, h.host));
+    ssh_args
+}
+
+/// Arguments for running `command` without a terminal. The host's own
+/// `command` and forwards are left out. Hosts without a password get
+/// `BatchMode` so a missing key fails instead of prompting.
+pub fn exec_args(h: &Host, command: &str) -> Vec<String> {
+    let mut ssh_args = vec!["-T".to_string()];
+    ssh_args.extend(common_args(h));
+    if h.password.is_none() {
+        ssh_args.push("-o".to_string());
+        ssh_args.push("BatchMode=yes".to_string());
+    }
+    ssh_args.push(format!("{}@{}", h.user, h.host));
+    ssh_args.push(command.to_string());
+    ssh_args
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TransferDirection {
+    Upload,
+    Download,
+}
+
+impl TransferDirection {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "u" | "up" | "upload" => Some(TransferDirection::Upload),
+            "d" | "down" | "download" => Some(TransferDirection::Download),
+            _ => None,
+        }
+    }
+}
+
+/// A file copy between this machine and a host. An empty `remote` is the
+/// remote home directory and an empty `local` the current directory.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Transfer {
+    pub direction: TransferDirection,
+    pub local: String,
+    pub remote: String,
+}
+
+/// Arguments for `scp`, with the same connection options as ssh. scp
+/// takes the port as `-P`; `-r` lets the same call copy directories.
+pub fn scp_args(h: &Host, transfer: &Transfer) -> Vec<String> {
+    let mut scp_args = vec!["-r".to_string()];
+    scp_args.extend(common_args_with_port_flag(h, "-P"));
+
+    let remote = format!("{}:{}", sftp_destination(h), transfer.remote);
+    let local = if transfer.local.is_empty() { ".".to_string() } else { transfer.local.clone() };
+    match transfer.direction {
+        TransferDirection::Upload => scp_args.extend([local, remote]),
+        TransferDirection::Download => scp_args.extend([remote, local]),
+    }
+    scp_args
+}
+
+/// Arguments for a control master that only holds the connection open, so
+/// sftp can run over `socket` without authenticating again.
+pub fn master_args(h: &Host, socket: &Path) -> Vec<String> {
+    let mut ssh_args = vec![
+        "-M".to_string(),
+        "-N".to_string(),
+        "-o".to_string(),
+        format!("ControlPath={}", socket.display()),
+        "-o".to_string(),
+        "ControlPersist=no".to_string(),
+        "-o".to_string(),
+        "ServerAliveInterval=15".to_string(),
+        "-o".to_string(),
+        "ServerAliveCountMax=3".to_string(),
+    ];
+    ssh_args.extend(common_args(h));
+    ssh_args.push(format!("{}@{}", h.user, h.host));
+    ssh_args
+}
+
+/// Options that share one connection per host through the master at
+/// `socket`. The first session becomes the master, which stays open for
+/// `persist` after the last session ends.
+pub fn multiplex_args(socket: &Path, persist: &str) -> Vec<String> {
+    vec![
+        "-o".to_string(),
+        "ControlMaster=auto".to_string(),
+        "-o".to_string(),
+        format!("ControlPath={}", socket.display()),
+        "-o".to_string(),
+        format!("ControlPersist={}", persist),
+    ]
+}
+
+/// Arguments sending a control command (`check`, `exit`) to the master at
+/// `socket`. ssh wants a destination but doesn't connect to it.
+pub fn control_args(h: &Host, socket: &Path, command: &str) -> Vec<String> {
+    vec![
+        "-O".to_string(),
+        command.to_string(),
+        "-o".to_string(),
+        format!("ControlPath={}", socket.display()),
+        format!("{}@{}", h.user, h.host),
+    ]
+}
+
+/// Arguments for `sftp -b -` reading commands from stdin over the master
+/// at `socket`. `BatchMode` keeps it from prompting if the master is gone.
+pub fn sftp_args(h: &Host, socket: &Path) -> Vec<String> {
+    let mut sftp_args = vec![
+        "-q".to_string(),
+        "-b".to_string(),
+        "-".to_string(),
+        "-o".to_string(),
+        format!("ControlPath={}", socket.display()),
+        "-o".to_string(),
+        "ControlMaster=no".to_string(),
+        "-o".to_string(),
+        "BatchMode=yes".to_string(),
+    ];
+    if let Some(port) = h.port {
+        sftp_args.push("-P".to_string());
+        sftp_args.push(port.to_string());
+    }
+    sftp_args.push(sftp_destination(h));
+    sftp_args
+}
+
+/// `user@host` as scp and sftp expect it, with IPv6 addresses bracketed
+/// so their colons aren't taken for the path separator.
+fn sftp_destination(h: &Host) -> String {
+    if h.host.contains(':') {
+        format!("{}@[{}]", h.user, h.host)
+    } else {
+        format!("{}@{}", h.user, h.host)
+    }
+}
+
+fn common_args(h: &Host) -> Vec<String> {
+    common_args_with_port_flag(h, "-p")
+}
+
+fn common_args_with_port_flag(h: &Host, port_flag: &str) -> Vec<String> {
+    let mut ssh_args = vec![
+        "-o".to_string(),
+        "ConnectTimeout=30".to_string(),
+        "-o".to_string(),
+        "StrictHostKeyChecking=accept-new".to_string(),
+    ];
+
+    if let Some(port) = h.port {
+        ssh_args.push(port_flag.to_string());
+        ssh_args.push(port.to_string());
+    }
+
+    if let Some(identity) = &h.identity_file {
+        ssh_args.push("-i".to_string());
+        ssh_args.push(identity.clone());
+    }
+
+    if h.identities_only {
+        ssh_args.push("-o".to_string());
+        ssh_args.push("IdentitiesOnly=yes".to_string());
+    }
+
+    if let Some(jump) = &h.jump {
+        ssh_args.push("-J".to_string());
+        ssh_args.push(jump.clone());
+    }
+
+    ssh_args
+}
+
+fn forward_args(h: &Host) -> Vec<String> {
+    h.forwards.iter()
+        .flat_map(|f| [f.flag().to_string(), f.spec()])
+        .collect()
+}
+
+pub fn command(program: &str, h: &Host, args: &[String]) -> Command {
+    command_with(program, h, args, password_method().as_ref())
+}
+
+/// Builds a `program` invocation for `h`. A stored password is handed over
+/// through the environment, never on the command line where `ps` and
+/// `/proc/*/cmdline` would expose it. One still encrypted because it didn't
+/// decrypt is never sent.
+pub fn command_with(program: &str, h: &Host, args: &[String], method: Option<&PasswordMethod>) -> Command {
+    let password = h.password.as_ref().filter(|pw| !vault::is_encrypted(pw));
+    match (password, method) {
+        (Some(pw), Some(PasswordMethod::Askpass(exe))) => {
+            let mut cmd = Command::new(program);
+            cmd.arg("-o").arg("NumberOfPasswordPrompts=1")
+                .args(args)
+                .env("SSH_ASKPASS", exe)
+                .env("SSH_ASKPASS_REQUIRE", "force")
+                .env(ASKPASS_ENV, "1")
+                .env(ASKPASS_TARGET_ENV, prompt_target(h))
+                .env(ASKPASS_PASSWORD_ENV, pw);
+            if let Some(identity) = prompt_identity(h) {
+                cmd.env(ASKPASS_IDENTITY_ENV, identity);
+            }
+            cmd
+        }
+        (Some(pw), Some(PasswordMethod::Sshpass)) => {
+            // `-P` makes sshpass wait for this host's own prompt rather than
+            // any line containing "assword".
+            let mut cmd = Command::new("sshpass");
+            cmd.arg("-e").arg("-P").arg(prompt_target(h)).arg(program).args(args).env("SSHPASS", pw);
+            cmd
+        }
+        _ => {
+            let mut cmd = Command::new(program);
+            cmd.args(args);
+            cmd
+        }
+    }
+}
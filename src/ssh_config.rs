@@ -0,0 +1,325 @@
+use crate::config::{Forward, ForwardKind, Host, Multiplex};
+use crate::multiplex;
+use dirs::home_dir;
+use std::env;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+const MAX_INCLUDE_DEPTH: usize = 16;
+
+struct Block {
+    patterns: Vec<String>,
+    options: Vec<(String, String)>,
+}
+
+impl Block {
+    fn matches(&self, alias: &str) -> bool {
+        let mut matched = false;
+        for pattern in &self.patterns {
+            if let Some(negated) = pattern.strip_prefix('!') {
+                if wildcard_match(negated, alias) {
+                    return false;
+                }
+            } else if wildcard_match(pattern, alias) {
+                matched = true;
+            }
+        }
+        matched
+    }
+
+    fn option(&self, key: &str) -> Option<&str> {
+        self.options.iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v.as_str())
+    }
+
+    fn options<'a>(&'a self, key: &'a str) -> impl Iterator<Item = &'a str> + 'a {
+        self.options.iter()
+            .filter(move |(k, _)| k == key)
+            .map(|(_, v)| v.as_str())
+    }
+}
+
+pub fn default_path() -> PathBuf {
+    ssh_dir().join("config")
+}
+
+fn ssh_dir() -> PathBuf {
+    home_dir().unwrap_or_else(|| PathBuf::from(".")).join(".ssh")
+}
+
+pub fn load(path: &Path) -> io::Result<Vec<Host>> {
+    let content = fs::read_to_string(path)?;
+    let base_dir = path.parent().map(Path::to_path_buf).unwrap_or_else(ssh_dir);
+    Ok(parse(&content, &base_dir))
+}
+
+/// Parses OpenSSH client config into hosts, one per concrete `Host` alias.
+/// Relative `Include` paths are resolved against `base_dir`.
+pub fn parse(content: &str, base_dir: &Path) -> Vec<Host> {
+    let mut blocks = vec![Block { patterns: vec!["*".into()], options: vec![] }];
+    collect_blocks(content, base_dir, 0, &mut blocks);
+
+    let mut aliases: Vec<&str> = Vec::new();
+    for block in &blocks {
+        for pattern in &block.patterns {
+            if pattern.starts_with('!') || pattern.contains(['*', '?']) {
+                continue;
+            }
+            if !aliases.contains(&pattern.as_str()) {
+                aliases.push(pattern);
+            }
+        }
+    }
+
+    aliases.into_iter().map(|alias| resolve(alias, &blocks)).collect()
+}
+
+fn collect_blocks(content: &str, base_dir: &Path, depth: usize, blocks: &mut Vec<Block>) {
+    for line in content.lines() {
+        let Some((key, args)) = split_line(line) else { continue };
+        match key.as_str() {
+            "host" => blocks.push(Block { patterns: args, options: vec![] }),
+            // Match conditions can't be evaluated offline, so skip their options.
+            "match" => blocks.push(Block { patterns: vec![], options: vec![] }),
+            "include" => {
+                if depth >= MAX_INCLUDE_DEPTH {
+                    continue;
+                }
+                for arg in &args {
+                    for path in expand_include(arg, base_dir) {
+                        if let Ok(included) = fs::read_to_string(&path) {
+                            collect_blocks(&included, base_dir, depth + 1, blocks);
+                        }
+                    }
+                }
+            }
+            _ => {
+                if let Some(block) = blocks.last_mut().filter(|_| !args.is_empty()) {
+                    block.options.push((key, args.join(" ")));
+                }
+            }
+        }
+    }
+}
+
+fn split_line(line: &str) -> Option<(String, Vec<String>)> {
+    let line = line.trim();
+    if line.is_empty() || line.starts_with('#') {
+        return None;
+    }
+    let key_end = line.find(|c: char| c.is_whitespace() || c == '=').unwrap_or(line.len());
+    let key = line[..key_end].to_lowercase();
+    let rest = line[key_end..].trim_start();
+    let rest = rest.strip_prefix('=').unwrap_or(rest);
+    Some((key, split_args(rest)))
+}
+
+fn split_args(s: &str) -> Vec<String> {
+    let mut args = Vec::new();
+    let mut current = String::new();
+    let mut in_quotes = false;
+    let mut has_token = false;
+    for c in s.chars() {
+        match c {
+            '"' => {
+                in_quotes = !in_quotes;
+                has_token = true;
+            }
+            c if c.is_whitespace() && !in_quotes => {
+                if has_token {
+                    args.push(std::mem::take(&mut current));
+                    has_token = false;
+                }
+            }
+            c => {
+                current.push(c);
+                has_token = true;
+            }
+        }
+    }
+    if has_token {
+        args.push(current);
+    }
+    args
+}
+
+fn expand_include(arg: &str, base_dir: &Path) -> Vec<PathBuf> {
+    let path = if let Some(rest) = arg.strip_prefix("~/") {
+        home_dir().unwrap_or_else(|| PathBuf::from(".")).join(rest)
+    } else if Path::new(arg).is_absolute() {
+        PathBuf::from(arg)
+    } else {
+        base_dir.join(arg)
+    };
+
+    let file_pattern = path.file_name().and_then(|n| n.to_str()).unwrap_or_default();
+    if !file_pattern.contains(['*', '?']) {
+        return vec![path];
+    }
+
+    let dir = path.parent().unwrap_or(base_dir);
+    let mut matches: Vec<PathBuf> = fs::read_dir(dir)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter(|entry| {
+            entry.file_name().to_str()
+                .map(|name| wildcard_match(file_pattern, name))
+                .unwrap_or(false)
+        })
+        .map(|entry| entry.path())
+        .collect();
+    matches.sort();
+    matches
+}
+
+/// Renders hosts as `Host` blocks suitable for an `Include`d OpenSSH config.
+/// Multiplexed hosts share their control socket with lazyssh.
+pub fn render(hosts: &[Host], multiplex: &Multiplex) -> String {
+    let mut out = String::from("# Generated by lazyssh from ~/.lazyssh/config.toml. Do not edit by hand.\n");
+    for h in hosts {
+        out.push('\n');
+        out.push_str(&format!("Host {}\n", alias_for(&h.name)));
+        out.push_str(&format!("    HostName {}\n", quote(&h.host.replace('%', "%%"))));
+        if !h.user.is_empty() {
+            out.push_str(&format!("    User {}\n", quote(&h.user)));
+        }
+        if let Some(port) = h.port {
+            out.push_str(&format!("    Port {}\n", port));
+        }
+        if let Some(identity) = &h.identity_file {
+            out.push_str(&format!("    IdentityFile {}\n", quote(identity)));
+        }
+        if h.identities_only {
+            out.push_str("    IdentitiesOnly yes\n");
+        }
+        if let Some(jump) = &h.jump {
+            out.push_str(&format!("    ProxyJump {}\n", proxy_jump(hosts, jump)));
+        }
+        if let Some(persist) = multiplex.persist_for(h).filter(|_| cfg!(unix)) {
+            out.push_str("    ControlMaster auto\n");
+            out.push_str(&format!("    ControlPath {}\n", quote(&multiplex::socket_path(h).display().to_string())));
+            out.push_str(&format!("    ControlPersist {}\n", persist));
+        }
+        for forward in &h.forwards {
+            let line = match (forward.kind, &forward.target) {
+                (ForwardKind::Local, Some(target)) => format!("LocalForward {} {}", forward.listen, target),
+                (ForwardKind::Remote, Some(target)) => format!("RemoteForward {} {}", forward.listen, target),
+                (_, _) => format!("DynamicForward {}", forward.listen),
+            };
+            out.push_str(&format!("    {}\n", line));
+        }
+    }
+    out
+}
+
+/// Named hops become the aliases they are exported under, so ssh picks up
+/// their own `IdentityFile` and `ProxyJump` from the same file.
+fn proxy_jump(hosts: &[Host], jump: &str) -> String {
+    jump.split(',')
+        .map(str::trim)
+        .filter(|part| !part.is_empty())
+        .map(|part| match hosts.iter().find(|h| h.name == part) {
+            Some(h) => alias_for(&h.name),
+            None => part.to_string(),
+        })
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+/// `name` as a `Host` alias. Whitespace becomes `-`, and the characters ssh
+/// reads as patterns, negations, lists or quotes become `_`, so the alias
+/// only ever matches this host.
+fn alias_for(name: &str) -> String {
+    name.split_whitespace()
+        .collect::<Vec<_>>()
+        .join("-")
+        .replace(['*', '?', '!', ',', '"'], "_")
+}
+
+fn quote(value: &str) -> String {
+    if value.contains(char::is_whitespace) {
+        format!("\"{}\"", value)
+    } else {
+        value.to_string()
+    }
+}
+
+fn resolve(alias: &str, blocks: &[Block]) -> Host {
+    // OpenSSH uses the first value obtained for each option.
+    let lookup = |key: &str| -> Option<String> {
+        blocks.iter()
+            .filter(|b| b.matches(alias))
+            .find_map(|b| b.option(key))
+            .map(str::to_string)
+    };
+
+    let host = lookup("hostname")
+        .map(|h| h.replace("%h", alias).replace("%%", "%"))
+        .unwrap_or_else(|| alias.to_string());
+    let user = lookup("user").unwrap_or_else(local_user);
+    let port = lookup("port").and_then(|p| p.parse().ok());
+    let identity_file = lookup("identityfile");
+    let identities_only = lookup("identitiesonly").is_some_and(|v| v.eq_ignore_ascii_case("yes"));
+    let jump = lookup("proxyjump").filter(|j| !j.eq_ignore_ascii_case("none"));
+    let control_persist = lookup("controlpersist").filter(|p| !p.eq_ignore_ascii_case("no"));
+
+    // Forwards accumulate across every matching block instead of first-wins.
+    let mut forwards = Vec::new();
+    for block in blocks.iter().filter(|b| b.matches(alias)) {
+        for (key, prefix) in [("localforward", "L"), ("remoteforward", "R"), ("dynamicforward", "D")] {
+            for value in block.options(key) {
+                let spec = value.split_whitespace().collect::<Vec<_>>().join(":");
+                if let Some(forward) = Forward::parse(&format!("{} {}", prefix, spec)) {
+                    forwards.push(forward);
+                }
+            }
+        }
+    }
+
+    Host {
+        name: alias.to_string(),
+        user,
+        host,
+        port,
+        identity_file,
+        identities_only,
+        jump,
+        control_persist,
+        forwards,
+        ..Default::default()
+    }
+}
+
+fn local_user() -> String {
+    env::var("USER")
+        .or_else(|_| env::var("USERNAME"))
+        .unwrap_or_default()
+}
+
+/// Matches `text` against an OpenSSH-style pattern supporting `*` and `?`.
+pub fn wildcard_match(pattern: &str, text: &str) -> bool {
+    let p: Vec<char> = pattern.chars().collect();
+    let t: Vec<char> = text.chars().collect();
+    let (mut pi, mut ti) = (0, 0);
+    let mut star: Option<(usize, usize)> = None;
+
+    while ti < t.len() {
+        if pi < p.len() && (p[pi] == '?' || p[pi] == t[ti]) {
+            pi += 1;
+            ti += 1;
+        } else if pi < p.len() && p[pi] == '*' {
+            star = Some((pi, ti));
+            pi += 1;
+        } else if let Some((sp, st)) = star {
+            pi = sp + 1;
+            ti = st + 1;
+            star = Some((sp, st + 1));
+        } else {
+            return false;
+        }
+    }
+    p[pi..].iter().all(|&c| c == '*')
+}
@@ -0,0 +1,229 @@
+use crate::config::{self, Forward, ForwardKind, Host};
+use crate::ssh;
+use std::io::{self, BufRead, BufReader};
+use std::collections::HashSet;
+use std::fs;
+use std::net::TcpListener;
+use std::process::{Child, Stdio};
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TunnelStatus {
+    Running,
+    Exited(Option<i32>),
+    Failed(String),
+}
+
+pub struct Tunnel {
+    pub host: Host,
+    pub pid: Option<u32>,
+    pub status: TunnelStatus,
+    pub started_at: Instant,
+    /// Each forward with whether its local port is currently bound.
+    /// `None` for remote forwards, whose port lives on the server.
+    pub ports: Vec<(Forward, Option<bool>)>,
+    last_error: Arc<Mutex<String>>,
+    child: Option<Child>,
+}
+
+impl Tunnel {
+    pub fn last_error(&self) -> String {
+        self.last_error.lock().map(|e| e.clone()).unwrap_or_default()
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.status == TunnelStatus::Running
+    }
+
+    fn kill(&mut self) {
+        if let Some(mut child) = self.child.take() {
+            let _ = child.kill();
+            let _ = child.wait();
+        }
+    }
+}
+
+/// Keeps `ssh -N` children running in the background while the TUI is
+/// open. Every tunnel is killed when the manager is dropped.
+#[derive(Default)]
+pub struct TunnelManager {
+    tunnels: Vec<Tunnel>,
+}
+
+impl TunnelManager {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn tunnels(&self) -> &[Tunnel] {
+        &self.tunnels
+    }
+
+    pub fn is_running(&self, name: &str) -> bool {
+        self.tunnels.iter().any(|t| t.host.name == name && t.is_running())
+    }
+
+    /// Starts the forwards of `h`, resolving its jump hosts against `hosts`.
+    /// A host that already has a tunnel gets it restarted instead.
+    pub fn start(&mut self, h: &Host, hosts: &[Host]) {
+        match self.tunnels.iter_mut().find(|t| t.host.name == h.name) {
+            Some(existing) => {
+                // Frees its ports before the new one checks them.
+                existing.kill();
+                *existing = spawn(h, hosts);
+            }
+            None => self.tunnels.push(spawn(h, hosts)),
+        }
+    }
+
+    pub fn restart(&mut self, idx: usize, hosts: &[Host]) {
+        if let Some(h) = self.tunnels.get(idx).map(|t| t.host.clone()) {
+            self.start(&h, hosts);
+        }
+    }
+
+    pub fn stop(&mut self, idx: usize) {
+        if idx < self.tunnels.len() {
+            self.tunnels.remove(idx).kill();
+        }
+    }
+
+    pub fn stop_all(&mut self) {
+        for tunnel in &mut self.tunnels {
+            tunnel.kill();
+        }
+        self.tunnels.clear();
+    }
+
+    /// Picks up exited children and re-checks which local ports are bound.
+    /// Where the listening sockets can't be read, a running tunnel counts
+    /// as bound: ssh exits when it can't bind a forward.
+    pub fn refresh(&mut self) {
+        let listening = listening_ports();
+        for tunnel in &mut self.tunnels {
+            if let Some(child) = tunnel.child.as_mut() {
+                match child.try_wait() {
+                    Ok(Some(status)) => {
+                        tunnel.status = TunnelStatus::Exited(status.code());
+                        tunnel.child = None;
+                    }
+                    Ok(None) => {}
+                    Err(e) => tunnel.status = TunnelStatus::Failed(e.to_string()),
+                }
+            }
+            let running = tunnel.is_running();
+            for (forward, bound) in &mut tunnel.ports {
+                *bound = local_port(forward)
+                    .map(|(_, port)| running && listening.as_ref().is_none_or(|ports| ports.contains(&port)));
+            }
+        }
+    }
+}
+
+impl Drop for TunnelManager {
+    fn drop(&mut self) {
+        self.stop_all();
+    }
+}
+
+fn spawn(h: &Host, hosts: &[Host]) -> Tunnel {
+    let mut tunnel = Tunnel {
+        host: h.clone(),
+        pid: None,
+        status: TunnelStatus::Running,
+        started_at: Instant::now(),
+        ports: h.forwards.iter().map(|f| (f.clone(), None)).collect(),
+        last_error: Arc::new(Mutex::new(String::new())),
+        child: None,
+    };
+
+    let resolved = match config::resolve_jump(hosts, h) {
+        Ok(resolved) => resolved,
+        Err(e) => {
+            tunnel.status = TunnelStatus::Failed(e.to_string());
+            return tunnel;
+        }
+    };
+    if resolved.password.is_some() && ssh::password_method().is_none() {
+        tunnel.status = TunnelStatus::Failed("no password helper (OpenSSH 8.4+ or sshpass)".into());
+        return tunnel;
+    }
+
+    if let Some((addr, port)) = h.forwards.iter().filter_map(local_port).find(|(addr, port)| !is_port_free(addr, *port)) {
+        tunnel.status = TunnelStatus::Failed(format!("{}:{} is already in use", addr, port));
+        return tunnel;
+    }
+
+    let mut cmd = ssh::command("ssh", &resolved, &ssh::tunnel_args(&resolved));
+    cmd.stdin(Stdio::null()).stdout(Stdio::null()).stderr(Stdio::piped());
+    match cmd.spawn() {
+        Ok(mut child) => {
+            tunnel.pid = Some(child.id());
+            if let Some(stderr) = child.stderr.take() {
+                let last_error = Arc::clone(&tunnel.last_error);
+                std::thread::spawn(move || capture_last_line(stderr, last_error));
+            }
+            tunnel.child = Some(child);
+        }
+        Err(e) => tunnel.status = TunnelStatus::Failed(e.to_string()),
+    }
+    tunnel
+}
+
+/// Keeps the last non-empty line of a child's stderr, for showing why it
+/// exited.
+pub fn capture_last_line(stderr: impl io::Read, last: Arc<Mutex<String>>) {
+    for line in BufReader::new(stderr).lines().map_while(Result::ok) {
+        if !line.trim().is_empty() {
+            if let Ok(mut last) = last.lock() {
+                *last = line;
+            }
+        }
+    }
+}
+
+/// The local address a forward listens on. Remote forwards listen on the
+/// server, so they have none.
+pub fn local_port(forward: &Forward) -> Option<(String, u16)> {
+    if forward.kind == ForwardKind::Remote {
+        return None;
+    }
+    let (addr, port) = match forward.listen.rsplit_once(':') {
+        Some((addr, port)) => (addr.trim_matches(|c| c == '[' || c == ']'), port),
+        None => ("127.0.0.1", forward.listen.as_str()),
+    };
+    let addr = match addr {
+        "" | "*" | "localhost" => "127.0.0.1",
+        other => other,
+    };
+    Some((addr.to_string(), port.parse().ok()?))
+}
+
+/// Checked once before ssh starts. Binding it later would race ssh for the
+/// port, and connecting would open a channel to the forward's target.
+pub fn is_port_free(addr: &str, port: u16) -> bool {
+    TcpListener::bind((addr, port)).is_ok()
+}
+
+/// Local TCP ports with a listening socket, read from `/proc/net/tcp*`
+/// without touching them. `None` where that isn't available.
+pub fn listening_ports() -> Option<HashSet<u16>> {
+    let mut ports = HashSet::new();
+    let mut found = false;
+    for table in ["/proc/net/tcp", "/proc/net/tcp6"] {
+        let Ok(contents) = fs::read_to_string(table) else { continue };
+        found = true;
+        ports.extend(parse_listening(&contents));
+    }
+    found.then_some(ports)
+}
+
+/// Ports in the LISTEN state (`0A`) of a `/proc/net/tcp` table.
+pub fn parse_listening(table: &str) -> impl Iterator<Item = u16> + '_ {
+    table.lines().skip(1).filter_map(|line| {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        let (_, port) = fields.get(1)?.rsplit_once(':')?;
+        (*fields.get(3)? == "0A").then(|| u16::from_str_radix(port, 16).ok()).flatten()
+    })
+}
@@ -1,5 +1,13 @@
-use crate::config::Host;
+use crate::backup::{self, Backup, DiffLine};
+use crate::config::{self, Config, Forward, Host, LoadError};
+use crate::filter;
+use crate::health::{self, HealthChecker, HealthStatus};
+use crate::history::{self, HostStats};
+use crate::multiplex;
 use crate::i18n::I18n;
+use crate::ssh::{Transfer, TransferDirection};
+use crate::tunnel::{Tunnel, TunnelManager, TunnelStatus};
+use crate::vault;
 use crossterm::{
     event::{self, Event, KeyCode, KeyEvent, KeyModifiers},
     execute,
@@ -10,21 +18,42 @@ use tui::{
     layout::{Constraint, Direction, Layout},
     style::{Color, Modifier, Style},
     text::{Span, Spans},
-    widgets::{Block, Borders, List, ListItem, Paragraph},
+    widgets::{Block, Borders, List, ListItem, Paragraph, Wrap},
     Terminal,
 };
+use std::cmp::Reverse;
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::fs;
 use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
 use clipboard::ClipboardProvider;
 
 pub enum Action {
     Connect(Host),
+    Tunnel(Host),
+    Exec(Vec<Host>),
+    Transfer(Host, Transfer),
+    Browse(Host),
+    CheckMaster(Host),
+    CloseMaster(Host),
+    Restore(PathBuf),
     Add(Host),
     Edit(usize, Host),
-    Delete(usize),
+    Delete(Vec<usize>),
+    Import,
     Copy,
     Quit,
 }
 
+/// What to do about a config file that failed to load.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LoadErrorChoice {
+    Edit,
+    Reload,
+    Quit,
+}
+
 pub struct Ui;
 
 pub struct FormField {
@@ -39,45 +68,494 @@ enum AppMode {
     Form {
         fields: Vec<FormField>,
         selected: usize,
-        editing_host_idx: Option<usize>,
+        kind: FormKind,
+        suggestions: Vec<String>,
     },
     ConfirmDelete {
-        host_idx: usize,
-        host_name: String,
+        host_indices: Vec<usize>,
+        host_names: Vec<String>,
+    },
+    Tunnels {
+        selected: usize,
+    },
+    /// Config backups, newest first, and what restoring the selected one
+    /// would change.
+    Backups {
+        backups: Vec<Backup>,
+        selected: usize,
+        preview: Vec<DiffLine>,
     },
+    Filter,
+    /// Every key of the host list, which doesn't fit in the help bar.
+    Help,
+}
+
+/// What submitting a form does; the `usize` is the host's index.
+#[derive(Clone, Copy)]
+enum FormKind {
+    Add,
+    Edit(usize),
+    Transfer(usize),
+}
+
+/// What carries over from one run of the TUI to the next.
+#[derive(Debug, Default)]
+pub struct Session {
+    pub sort: SortMode,
+    /// Shown on the next run, e.g. why the last change wasn't saved.
+    pub status: Option<String>,
+}
+
+/// Order of hosts within each group of the list.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SortMode {
+    #[default]
+    Config,
+    Name,
+    Recent,
+    Frequent,
+}
+
+impl SortMode {
+    pub fn next(self) -> Self {
+        match self {
+            SortMode::Config => SortMode::Name,
+            SortMode::Name => SortMode::Recent,
+            SortMode::Recent => SortMode::Frequent,
+            SortMode::Frequent => SortMode::Config,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            SortMode::Config => "config order",
+            SortMode::Name => "name",
+            SortMode::Recent => "most recent",
+            SortMode::Frequent => "most frequent",
+        }
+    }
+}
+
+/// Hosts without a group are listed under this node, unless no host has
+/// one. Its path can't be a real group's, so a group named "Ungrouped"
+/// gets its own node.
+pub const UNGROUPED: &str = "Ungrouped";
+pub const UNGROUPED_PATH: &str = "/";
+
+/// The host list's keys for the `?` panel. The help bar only has room for
+/// the most common ones.
+const NORMAL_KEYS: &[(&str, &str)] = &[
+    ("↑/↓/j/k", "Navigate"),
+    ("←/→/h/l", "Collapse/Expand group"),
+    ("/", "Filter (tag:NAME, !tag:NAME)"),
+    ("Enter", "Connect"),
+    ("f", "Transfer a file"),
+    ("F", "Browse files"),
+    ("t", "Tunnel"),
+    ("b", "Background tunnel"),
+    ("T", "Tunnels"),
+    ("m / M", "Check / close master"),
+    ("x", "Run a command"),
+    ("Space/V/*", "Select host / range / all"),
+    ("s", "Sort"),
+    ("a / e / d", "Add / edit / delete"),
+    ("i", "Import ~/.ssh/config"),
+    ("R", "Backups"),
+    ("y / p", "Copy / paste"),
+    ("?", "This help"),
+    ("q/Ctrl+C", "Quit"),
+];
+
+/// Checking for live masters connects to a socket per host, so it runs
+/// less often than the tunnel refresh.
+const MASTERS_CHECK_INTERVAL: Duration = Duration::from_secs(5);
+
+/// One visible line of the host tree.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ListRow {
+    Group { path: String, name: String, depth: usize, count: usize, collapsed: bool },
+    Host { idx: usize, depth: usize },
+}
+
+struct GroupNode {
+    name: String,
+    path: String,
+    groups: Vec<GroupNode>,
+    hosts: Vec<usize>,
+}
+
+impl GroupNode {
+    fn new(name: &str, path: String) -> Self {
+        Self { name: name.to_string(), path, groups: vec![], hosts: vec![] }
+    }
+
+    fn child(&mut self, name: &str) -> &mut GroupNode {
+        let pos = match self.groups.iter().position(|g| g.name == name) {
+            Some(pos) => pos,
+            None => {
+                let path = if self.path.is_empty() { name.to_string() } else { format!("{}/{}", self.path, name) };
+                self.groups.push(GroupNode::new(name, path));
+                self.groups.len() - 1
+            }
+        };
+        &mut self.groups[pos]
+    }
+
+    fn count(&self) -> usize {
+        self.hosts.len() + self.groups.iter().map(GroupNode::count).sum::<usize>()
+    }
+
+    fn flatten(&self, depth: usize, collapsed: &HashSet<String>, rows: &mut Vec<ListRow>) {
+        for group in &self.groups {
+            let is_collapsed = collapsed.contains(&group.path);
+            rows.push(ListRow::Group {
+                path: group.path.clone(),
+                name: group.name.clone(),
+                depth,
+                count: group.count(),
+                collapsed: is_collapsed,
+            });
+            if !is_collapsed {
+                group.flatten(depth + 1, collapsed, rows);
+            }
+        }
+        for &idx in &self.hosts {
+            rows.push(ListRow::Host { idx, depth });
+        }
+    }
 }
 
 pub struct AppState {
     pub hosts: Vec<Host>,
+    /// Index into `hosts` of the selected host, whatever row it is drawn on.
     pub list_index: usize,
+    /// Index into `rows()` of the highlighted line.
+    pub cursor: usize,
+    pub collapsed: HashSet<String>,
+    /// While non-blank the tree is replaced by the matching hosts, best first.
+    pub filter: String,
+    /// Indices into `hosts` picked with space, `V` or `*`.
+    pub selection: BTreeSet<usize>,
+    pub sort: SortMode,
+    /// Names of hosts with a live control master.
+    pub masters: HashSet<String>,
+    /// Connection counts and times by host name, for sorting and details.
+    pub stats: HashMap<String, HostStats>,
+    /// Row where the last selection toggle happened, the start for `V`.
+    anchor: Option<usize>,
     mode: AppMode,
-    clipboard: Option<Host>,
+    clipboard: Vec<Host>,
+    /// Shown in place of the key help until the next key press.
+    pub status: Option<String>,
 }
 
 impl AppState {
     pub fn new(hosts: Vec<Host>) -> Self {
-        Self {
+        let mut app = Self {
             hosts,
             list_index: 0,
+            cursor: 0,
+            collapsed: HashSet::new(),
+            filter: String::new(),
+            selection: BTreeSet::new(),
+            sort: SortMode::Config,
+            masters: HashSet::new(),
+            stats: HashMap::new(),
+            anchor: None,
             mode: AppMode::Normal,
-            clipboard: None,
+            clipboard: vec![],
+            status: None,
+        };
+        app.cursor = app.rows().iter()
+            .position(|row| matches!(row, ListRow::Host { .. }))
+            .unwrap_or(0);
+        app.sync_list_index();
+        app
+    }
+
+    /// Host indices in `sort` order. Sorting is stable, so ties and hosts
+    /// that were never connected to keep their config order.
+    fn host_order(&self) -> Vec<usize> {
+        let mut order: Vec<usize> = (0..self.hosts.len()).collect();
+        let stats = |idx: usize| self.stats.get(&self.hosts[idx].name).copied().unwrap_or_default();
+        match self.sort {
+            SortMode::Config => {}
+            SortMode::Name => order.sort_by_key(|&idx| self.hosts[idx].name.to_lowercase()),
+            SortMode::Recent => order.sort_by_key(|&idx| Reverse(stats(idx).last)),
+            SortMode::Frequent => order.sort_by_key(|&idx| (Reverse(stats(idx).count), Reverse(stats(idx).last))),
+        }
+        order
+    }
+
+    /// The host tree in display order: subgroups before hosts at every
+    /// level, both in the order they first appear in `sort` order, with
+    /// ungrouped hosts last.
+    pub fn rows(&self) -> Vec<ListRow> {
+        if !self.filter.trim().is_empty() {
+            return filter::filter_hosts(&self.hosts, &self.filter)
+                .into_iter()
+                .map(|m| ListRow::Host { idx: m.idx, depth: 0 })
+                .collect();
+        }
+
+        let mut root = GroupNode::new("", String::new());
+        let mut ungrouped = Vec::new();
+        for idx in self.host_order() {
+            let path = self.hosts[idx].group_path();
+            if path.is_empty() {
+                ungrouped.push(idx);
+                continue;
+            }
+            let node = path.iter().fold(&mut root, |node, name| node.child(name));
+            node.hosts.push(idx);
+        }
+        if root.groups.is_empty() {
+            root.hosts = ungrouped;
+        } else if !ungrouped.is_empty() {
+            let mut node = GroupNode::new(UNGROUPED, UNGROUPED_PATH.to_string());
+            node.hosts = ungrouped;
+            root.groups.push(node);
+        }
+
+        let mut rows = Vec::new();
+        root.flatten(0, &self.collapsed, &mut rows);
+        rows
+    }
+
+    /// Index into `hosts` of the host under the cursor, if it's on a host.
+    pub fn selected_index(&self) -> Option<usize> {
+        match self.rows().get(self.cursor) {
+            Some(ListRow::Host { idx, .. }) => Some(*idx),
+            _ => None,
         }
     }
 
     pub fn selected_host(&self) -> Option<&Host> {
-        self.hosts.get(self.list_index)
+        self.selected_index().and_then(|idx| self.hosts.get(idx))
+    }
+
+    /// Replaces the filter query and puts the cursor on the top match.
+    pub fn set_filter(&mut self, query: &str) {
+        self.filter = query.to_string();
+        self.cursor = 0;
+        if self.filter.trim().is_empty() {
+            // Back to the tree: land on the host that was selected.
+            if let Some(pos) = self.rows().iter().position(|row| matches!(row, ListRow::Host { idx, .. } if *idx == self.list_index)) {
+                self.cursor = pos;
+            }
+        }
+        self.sync_list_index();
+    }
+
+    /// Changes the sort and keeps the cursor on the selected host.
+    pub fn set_sort(&mut self, sort: SortMode) {
+        self.sort = sort;
+        if let Some(pos) = self.rows().iter().position(|row| matches!(row, ListRow::Host { idx, .. } if *idx == self.list_index)) {
+            self.cursor = pos;
+        }
+        self.sync_list_index();
     }
 
     pub fn move_next(&mut self) {
-        if self.list_index + 1 < self.hosts.len() { self.list_index += 1; }
+        if self.cursor + 1 < self.rows().len() { self.cursor += 1; }
+        self.sync_list_index();
     }
 
     pub fn move_prev(&mut self) {
-        if self.list_index > 0 { self.list_index -= 1; }
+        if self.cursor > 0 { self.cursor -= 1; }
+        self.sync_list_index();
+    }
+
+    /// Collapses the group under the cursor, or moves up to the enclosing
+    /// group when the cursor is on a host or an already collapsed group.
+    pub fn collapse(&mut self) {
+        let rows = self.rows();
+        let (depth, expanded_group) = match rows.get(self.cursor) {
+            Some(ListRow::Group { path, depth, collapsed: false, .. }) => (*depth, Some(path.clone())),
+            Some(ListRow::Group { depth, .. }) | Some(ListRow::Host { depth, .. }) => (*depth, None),
+            None => return,
+        };
+        match expanded_group {
+            Some(path) => {
+                self.collapsed.insert(path);
+            }
+            None => {
+                if let Some(parent) = rows[..self.cursor].iter().rposition(|row| {
+                    matches!(row, ListRow::Group { depth: d, .. } if *d + 1 == depth)
+                }) {
+                    self.cursor = parent;
+                }
+            }
+        }
+    }
+
+    pub fn expand(&mut self) {
+        if let Some(ListRow::Group { path, .. }) = self.rows().get(self.cursor) {
+            self.collapsed.remove(path);
+        }
+    }
+
+    pub fn toggle_group(&mut self) {
+        if let Some(ListRow::Group { path, collapsed, .. }) = self.rows().get(self.cursor) {
+            if *collapsed {
+                self.collapsed.remove(path);
+            } else {
+                self.collapsed.insert(path.clone());
+            }
+        }
+    }
+
+    /// The hosts an action applies to: the selection, or else the host
+    /// under the cursor.
+    pub fn targets(&self) -> Vec<usize> {
+        if self.selection.is_empty() {
+            self.selected_index().into_iter().collect()
+        } else {
+            self.selection.iter().copied().collect()
+        }
+    }
+
+    /// Toggles the host under the cursor, or every host in the group under
+    /// the cursor (including collapsed subgroups).
+    pub fn toggle_selection(&mut self) {
+        let members = match self.rows().get(self.cursor) {
+            Some(ListRow::Host { idx, .. }) => vec![*idx],
+            Some(ListRow::Group { path, .. }) => self.group_members(path),
+            None => return,
+        };
+        self.anchor = Some(self.cursor);
+        self.set_selected(&members);
+    }
+
+    /// Selects every host row between the last toggled row and the cursor.
+    pub fn select_range(&mut self) {
+        let Some(anchor) = self.anchor else {
+            self.toggle_selection();
+            return;
+        };
+        let (from, to) = (anchor.min(self.cursor), anchor.max(self.cursor));
+        for row in self.rows().iter().take(to + 1).skip(from) {
+            if let ListRow::Host { idx, .. } = row {
+                self.selection.insert(*idx);
+            }
+        }
+    }
+
+    /// Selects every visible host, or clears them if all already are.
+    pub fn select_all_visible(&mut self) {
+        let visible: Vec<usize> = self.rows().iter()
+            .filter_map(|row| match row {
+                ListRow::Host { idx, .. } => Some(*idx),
+                ListRow::Group { .. } => None,
+            })
+            .collect();
+        self.set_selected(&visible);
+    }
+
+    pub fn clear_selection(&mut self) {
+        self.selection.clear();
+        self.anchor = None;
+    }
+
+    fn set_selected(&mut self, members: &[usize]) {
+        if members.iter().all(|idx| self.selection.contains(idx)) {
+            for idx in members {
+                self.selection.remove(idx);
+            }
+        } else {
+            self.selection.extend(members);
+        }
+    }
+
+    fn group_members(&self, path: &str) -> Vec<usize> {
+        let wanted: Vec<&str> = path.split('/').collect();
+        self.hosts.iter()
+            .enumerate()
+            .filter(|(_, h)| {
+                let group = h.group_path();
+                if path == UNGROUPED_PATH { group.is_empty() } else { group.starts_with(&wanted) }
+            })
+            .map(|(idx, _)| idx)
+            .collect()
+    }
+
+    fn sync_list_index(&mut self) {
+        if let Some(ListRow::Host { idx, .. }) = self.rows().get(self.cursor) {
+            self.list_index = *idx;
+        }
     }
 }
 
 impl Ui {
+    /// Shows why the config at `path` didn't load, with the offending line,
+    /// until the user picks what to do.
+    pub fn show_load_error(path: &Path, error: &LoadError) -> io::Result<LoadErrorChoice> {
+        enable_raw_mode()?;
+        let mut stdout = io::stdout();
+        execute!(stdout, EnterAlternateScreen)?;
+        let mut terminal = Terminal::new(CrosstermBackend::new(stdout))?;
+
+        let label = Style::default().fg(Color::Gray);
+        let mut text = vec![
+            Spans::from(vec![Span::styled("File:     ", label), Span::raw(path.display().to_string())]),
+        ];
+        if let (Some(line), Some(column)) = (error.line, error.column) {
+            text.push(Spans::from(vec![Span::styled("Position: ", label), Span::raw(format!("line {}, column {}", line, column))]));
+        }
+        if let Some(field) = &error.field {
+            text.push(Spans::from(vec![Span::styled("Field:    ", label), Span::raw(field.clone())]));
+        }
+        text.push(Spans::from(vec![
+            Span::styled("Error:    ", label),
+            Span::styled(error.message.clone(), Style::default().fg(Color::Red).add_modifier(Modifier::BOLD)),
+        ]));
+        if let (Some(source_line), Some(line), Some(column)) = (&error.source_line, error.line, error.column) {
+            let gutter = format!("{:>5} │ ", line);
+            text.push(Spans::from(""));
+            text.push(Spans::from(vec![Span::styled(gutter.clone(), label), Span::styled(source_line.clone(), Style::default().fg(Color::Yellow))]));
+            text.push(Spans::from(Span::styled(
+                format!("{}{}^", " ".repeat(gutter.chars().count()), " ".repeat(column - 1)),
+                Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+            )));
+        }
+        text.push(Spans::from(""));
+        text.push(Spans::from("Nothing is saved until the file loads again, so no hosts are lost."));
+
+        let choice = loop {
+            terminal.draw(|f| {
+                let chunks = Layout::default()
+                    .direction(Direction::Vertical)
+                    .constraints([Constraint::Min(1), Constraint::Length(3)].as_ref())
+                    .split(f.size());
+                let body = Paragraph::new(text.clone())
+                    .wrap(Wrap { trim: false })
+                    .block(
+                        Block::default()
+                            .borders(Borders::ALL)
+                            .border_style(Style::default().fg(Color::Red))
+                            .title(Span::styled("⚠ Config failed to load", Style::default().fg(Color::Red).add_modifier(Modifier::BOLD)))
+                    );
+                f.render_widget(body, chunks[0]);
+                let help = Paragraph::new("  e: Open in $EDITOR  │  r: Reload  │  q/Esc: Quit")
+                    .style(Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD))
+                    .block(Block::default().borders(Borders::ALL).border_style(Style::default().fg(Color::Cyan)));
+                f.render_widget(help, chunks[1]);
+            })?;
+
+            if let Event::Key(KeyEvent { code, modifiers, .. }) = event::read()? {
+                match code {
+                    KeyCode::Char('e') => break LoadErrorChoice::Edit,
+                    KeyCode::Char('r') => break LoadErrorChoice::Reload,
+                    KeyCode::Char('c') if modifiers.contains(KeyModifiers::CONTROL) => break LoadErrorChoice::Quit,
+                    KeyCode::Char('q') | KeyCode::Esc => break LoadErrorChoice::Quit,
+                    _ => {}
+                }
+            }
+        };
+        Self::exit_tui(&mut terminal)?;
+        Ok(choice)
+    }
+
     fn exit_tui(terminal: &mut Terminal<CrosstermBackend<io::Stdout>>) -> io::Result<()> {
         disable_raw_mode()?;
         execute!(terminal.backend_mut(), LeaveAlternateScreen)?;
@@ -117,46 +595,296 @@ impl Ui {
             .unwrap_or(value.len())
     }
 
-    fn validate_and_exit_on_error(host: &Host, terminal: &mut Terminal<CrosstermBackend<io::Stdout>>, i18n_ref: &I18n) -> io::Result<()> {
-        if host.user.is_empty() || host.host.is_empty() {
-            Self::exit_tui(terminal)?;
-            eprintln!("{}", i18n_ref.invalid_host_format());
-            std::process::exit(1);
-        }
-        Ok(())
+    fn is_complete(host: &Host) -> bool {
+        !host.user.is_empty() && !host.host.is_empty()
     }
 
-    fn save_form_and_exit<F>(
+    /// Leaves the TUI and hands the form's result to `on_action`. Returns
+    /// false, staying in the form, when a transfer form isn't complete or
+    /// the password is refused, with the reason in `status`.
+    fn submit_form<F>(
         fields: &[FormField],
-        editing_host_idx: Option<usize>,
+        kind: FormKind,
+        hosts: &[Host],
+        status: &mut Option<String>,
+        i18n: &I18n,
         terminal: &mut Terminal<CrosstermBackend<io::Stdout>>,
         on_action: &mut F,
-    ) -> io::Result<()>
+    ) -> io::Result<bool>
     where
         F: FnMut(Action),
     {
-        let host = Self::create_host_from_fields(fields);
-        Self::exit_tui(terminal)?;
-        if let Some(idx) = editing_host_idx {
-            on_action(Action::Edit(idx, host));
-        } else {
-            on_action(Action::Add(host));
+        let reserved = |host: &Host, before: Option<&Host>| {
+            host.password.as_deref().is_some_and(vault::is_encrypted)
+                && before.is_none_or(|b| b.password != host.password)
+        };
+        let action = match kind {
+            FormKind::Add => Action::Add(Self::create_host_from_fields(fields)),
+            FormKind::Edit(idx) => {
+                let mut host = hosts[idx].clone();
+                Self::apply_fields(&mut host, fields);
+                Action::Edit(idx, host)
+            }
+            FormKind::Transfer(idx) => match Self::transfer_from_fields(fields) {
+                Some(transfer) => Action::Transfer(hosts[idx].clone(), transfer),
+                None => return Ok(false),
+            },
+        };
+        let rejected = match (&action, kind) {
+            (Action::Add(host), _) => reserved(host, None),
+            (Action::Edit(_, host), FormKind::Edit(idx)) => reserved(host, Some(&hosts[idx])),
+            _ => false,
+        };
+        if rejected {
+            *status = Some(i18n.password_reserved_prefix().to_string());
+            return Ok(false);
         }
-        Ok(())
+        Self::exit_tui(terminal)?;
+        on_action(action);
+        Ok(true)
+    }
+
+    fn transfer_fields() -> Vec<FormField> {
+        ["Direction", "Local", "Remote"].iter()
+            .map(|label| FormField {
+                label: label.to_string(),
+                value: if *label == "Direction" { "upload".into() } else { String::new() },
+                cursor_pos: if *label == "Direction" { "upload".len() } else { 0 },
+                is_multiline: false,
+            })
+            .collect()
+    }
+
+    /// Reads a transfer form. Uploads need a local path; downloads need a
+    /// remote one.
+    pub fn transfer_from_fields(fields: &[FormField]) -> Option<Transfer> {
+        let value = |label: &str| fields.iter()
+            .find(|f| f.label == label)
+            .map(|f| f.value.trim().to_string())
+            .unwrap_or_default();
+        let transfer = Transfer {
+            direction: TransferDirection::parse(&value("Direction"))?,
+            local: value("Local"),
+            remote: value("Remote"),
+        };
+        let missing = match transfer.direction {
+            TransferDirection::Upload => transfer.local.is_empty(),
+            TransferDirection::Download => transfer.remote.is_empty(),
+        };
+        (!missing).then_some(transfer)
     }
 
     pub fn create_host_from_fields(fields: &[FormField]) -> Host {
-        Host {
-            name: fields[0].value.clone(),
-            user: fields[1].value.clone(),
-            host: fields[2].value.clone(),
-            port: Some(fields[3].value.parse().unwrap_or(22)),
-            password: if fields[4].value.is_empty() { None } else { Some(fields[4].value.clone()) },
-            command: if fields[5].value.is_empty() { None } else { Some(fields[5].value.clone()) },
+        let mut host = Host::default();
+        Self::apply_fields(&mut host, fields);
+        host
+    }
+
+    fn apply_fields(host: &mut Host, fields: &[FormField]) {
+        for field in fields {
+            let value = field.value.clone();
+            let optional = if value.is_empty() { None } else { Some(value.clone()) };
+            match field.label.as_str() {
+                "Name" => host.name = value,
+                "Tags" => host.tags = config::parse_tags(&value),
+                "Group" => {
+                    host.group = Some(value);
+                    host.group = Some(host.group_path().join("/")).filter(|g| !g.is_empty());
+                }
+                "User" => host.user = value,
+                "Host" => host.host = value,
+                "Port" => host.port = Some(value.parse().unwrap_or(22)),
+                "Password" => host.password = optional,
+                "Command" => host.command = optional,
+                "Identity" => host.identity_file = optional,
+                "Jump" => host.jump = optional,
+                "Persist" => host.control_persist = optional,
+                "Forwards" => host.forwards = value.lines().filter_map(Forward::parse).collect(),
+                "Key Only" => host.identities_only = matches!(value.trim().to_lowercase().as_str(), "y" | "yes" | "true" | "1"),
+                _ => {}
+            }
+        }
+    }
+
+    fn form_fields(h: &Host) -> Vec<FormField> {
+        let field = |label: &str, value: String, is_multiline: bool| FormField {
+            label: label.into(),
+            cursor_pos: value.len(),
+            value,
+            is_multiline,
+        };
+        vec![
+            field("Name", h.name.clone(), false),
+            field("Group", h.group.clone().unwrap_or_default(), false),
+            field("Tags", h.tags.join(", "), false),
+            field("User", h.user.clone(), false),
+            field("Host", h.host.clone(), false),
+            field("Port", h.port.unwrap_or(22).to_string(), false),
+            field("Password", h.password.clone().unwrap_or_default(), false),
+            field("Identity", h.identity_file.clone().unwrap_or_default(), false),
+            field("Key Only", if h.identities_only { "yes" } else { "no" }.to_string(), false),
+            field("Jump", h.jump.clone().unwrap_or_default(), false),
+            field("Persist", h.control_persist.clone().unwrap_or_default(), false),
+            field("Forwards", h.forwards.iter().map(|f| f.to_string()).collect::<Vec<_>>().join("\n"), true),
+            field("Command", h.command.clone().unwrap_or_default(), true),
+        ]
+    }
+
+    fn suggestions_for(field: &FormField, hosts: &[Host]) -> Vec<String> {
+        match (field.label.as_str(), dirs::home_dir()) {
+            ("Identity", Some(home)) => Self::complete_identity_path(&field.value, &home),
+            ("Tags", _) => Self::complete_tags(&field.value, hosts),
+            _ => vec![],
+        }
+    }
+
+    /// Completes the last tag being typed from tags already used on other
+    /// hosts. Candidates are whole field values, like the path completion.
+    pub fn complete_tags(input: &str, hosts: &[Host]) -> Vec<String> {
+        let split = input.rfind(|c: char| c == ',' || c.is_whitespace()).map(|i| i + 1).unwrap_or(0);
+        let (head, prefix) = input.split_at(split);
+        if prefix.is_empty() {
+            return vec![];
+        }
+        let mut skip = config::parse_tags(head);
+        let mut candidates: Vec<String> = Vec::new();
+        for tag in hosts.iter().flat_map(|h| &h.tags) {
+            if skip.iter().any(|t| t.eq_ignore_ascii_case(tag)) || !tag.to_lowercase().starts_with(&prefix.to_lowercase()) {
+                continue;
+            }
+            skip.push(tag.clone());
+            candidates.push(format!("{}{}", head, tag));
+        }
+        candidates.sort();
+        candidates
+    }
+
+    /// Lists private key candidates for a partially typed identity path.
+    /// A bare file name or empty input is looked up in `~/.ssh`.
+    pub fn complete_identity_path(input: &str, home: &Path) -> Vec<String> {
+        let (dir_part, file_prefix) = match input.rfind('/') {
+            Some(pos) => (&input[..=pos], &input[pos + 1..]),
+            None => ("~/.ssh/", input),
+        };
+        let dir = match dir_part.strip_prefix("~/") {
+            Some(rest) => home.join(rest),
+            None => PathBuf::from(dir_part),
+        };
+
+        let mut candidates: Vec<String> = fs::read_dir(&dir)
+            .into_iter()
+            .flatten()
+            .flatten()
+            .filter_map(|entry| {
+                let name = entry.file_name().into_string().ok()?;
+                let is_dir = entry.path().is_dir();
+                let skip = (name.starts_with('.') && !file_prefix.starts_with('.'))
+                    || name.ends_with(".pub")
+                    || name.starts_with("known_hosts")
+                    || name.starts_with("authorized_keys")
+                    || (name == "config" && !is_dir);
+                if skip || !name.starts_with(file_prefix) {
+                    return None;
+                }
+                Some(format!("{}{}{}", dir_part, name, if is_dir { "/" } else { "" }))
+            })
+            .collect();
+        candidates.sort();
+        candidates
+    }
+
+    /// Extends `field` to the longest prefix shared by all suggestions.
+    /// Returns false when there is nothing to add.
+    fn complete_field(field: &mut FormField, suggestions: &[String]) -> bool {
+        let Some(first) = suggestions.first() else { return false };
+        let common = suggestions.iter().skip(1).fold(first.as_str(), |acc, s| {
+            let len = acc.char_indices()
+                .zip(s.chars())
+                .take_while(|((_, a), b)| a == b)
+                .last()
+                .map(|((i, c), _)| i + c.len_utf8())
+                .unwrap_or(0);
+            &acc[..len]
+        });
+        if field.value.is_empty() || common.len() <= field.value.len() {
+            return false;
+        }
+        field.value = common.to_string();
+        field.cursor_pos = field.value.len();
+        true
+    }
+
+    /// The diff from the current config to `backup`.
+    fn backup_preview(backup: Option<&Backup>) -> Vec<DiffLine> {
+        let Some(backup) = backup else { return vec![] };
+        let current = fs::read_to_string(Config::path()).unwrap_or_default();
+        let restored = fs::read_to_string(&backup.path).unwrap_or_default();
+        backup::diff(&current, &restored, 2)
+    }
+
+    fn diff_items(preview: &[DiffLine]) -> Vec<ListItem<'static>> {
+        if preview.iter().all(|line| matches!(line, DiffLine::Skipped(_))) {
+            return vec![ListItem::new("  (same as the current config)").style(Style::default().fg(Color::DarkGray))];
         }
+        preview.iter().map(|line| match line {
+            DiffLine::Same(text) => ListItem::new(format!("  {}", text)).style(Style::default().fg(Color::Gray)),
+            DiffLine::Removed(text) => ListItem::new(format!("- {}", text)).style(Style::default().fg(Color::Red)),
+            DiffLine::Added(text) => ListItem::new(format!("+ {}", text)).style(Style::default().fg(Color::Green)),
+            DiffLine::Skipped(n) => ListItem::new(format!("  ⋯ {} unchanged", n)).style(Style::default().fg(Color::DarkGray)),
+        }).collect()
     }
 
-    pub fn run<F>(hosts: Vec<Host>, i18n: I18n, mut on_action: F) -> io::Result<()>
+    fn tunnel_items(tunnels: &[Tunnel], selected: usize) -> Vec<ListItem<'static>> {
+        let mut items = Vec::new();
+        for (idx, t) in tunnels.iter().enumerate() {
+            let (state, color) = match &t.status {
+                TunnelStatus::Running => {
+                    let secs = t.started_at.elapsed().as_secs();
+                    (format!("● up {:02}:{:02}:{:02}", secs / 3600, secs / 60 % 60, secs % 60), Color::Green)
+                }
+                TunnelStatus::Exited(Some(code)) => (format!("○ exited ({})", code), Color::Red),
+                TunnelStatus::Exited(None) => ("○ killed".to_string(), Color::Red),
+                TunnelStatus::Failed(e) => (format!("⚠ {}", e), Color::Red),
+            };
+            let pid = t.pid.map(|p| format!("pid {}", p)).unwrap_or_else(|| "no pid".to_string());
+            let marker = if idx == selected { "▶" } else { " " };
+            let mut style = Style::default().fg(color);
+            if idx == selected {
+                style = style.add_modifier(Modifier::BOLD);
+            }
+            items.push(ListItem::new(format!("{} {}  {}  {}", marker, truncate(&t.host.name, 20), pid, state)).style(style));
+
+            for (forward, bound) in &t.ports {
+                let port_state = match bound {
+                    Some(true) => "✓ bound",
+                    Some(false) => "✗ not bound",
+                    None => "(remote)",
+                };
+                items.push(ListItem::new(format!("    {:32} {}", truncate(&forward.to_string(), 32), port_state))
+                    .style(Style::default().fg(Color::White)));
+            }
+            let last_error = t.last_error();
+            if !t.is_running() && !last_error.is_empty() {
+                items.push(ListItem::new(format!("    {}", truncate(&last_error, 50)))
+                    .style(Style::default().fg(Color::DarkGray)));
+            }
+        }
+        if items.is_empty() {
+            items.push(ListItem::new("  No tunnels running. Press 'b' on a host with forwards.")
+                .style(Style::default().fg(Color::DarkGray)));
+        }
+        items
+    }
+
+    pub fn run<F>(
+        hosts: Vec<Host>,
+        i18n: I18n,
+        tunnels: &mut TunnelManager,
+        health: &HealthChecker,
+        stats: HashMap<String, HostStats>,
+        session: &mut Session,
+        mut on_action: F,
+    ) -> io::Result<()>
     where F: FnMut(Action)
     {
         enable_raw_mode()?;
@@ -166,14 +894,88 @@ impl Ui {
         let mut terminal = Terminal::new(backend)?;
 
         let mut app = AppState::new(hosts);
+        app.stats = stats;
+        app.set_sort(session.sort);
+        app.status = session.status.take();
+        let mut last_refresh = Instant::now();
+        let mut last_masters_check = Instant::now();
+        tunnels.refresh();
+        app.masters = multiplex::live_masters(&app.hosts);
 
         loop {
+            if last_refresh.elapsed() >= Duration::from_secs(1) {
+                tunnels.refresh();
+                last_refresh = Instant::now();
+            }
+            if last_masters_check.elapsed() >= MASTERS_CHECK_INTERVAL {
+                app.masters = multiplex::live_masters(&app.hosts);
+                last_masters_check = Instant::now();
+            }
             let i18n_ref = &i18n;
             terminal.draw(|f| {
                 let size = f.size();
+                let help_text = match &app.mode {
+                    AppMode::Normal => {
+                        vec![
+                            "  ↑/↓: Navigate  │  Enter: Connect  │  /: Filter  │  a/e/d: Add/Edit/Delete  │  ?: All Keys  │  q: Quit"
+                        ]
+                    },
+                    AppMode::Help => {
+                        vec![
+                            "  Esc/?: Back"
+                        ]
+                    },
+                    AppMode::ConfirmDelete { .. } => {
+                        vec![
+                            i18n_ref.confirm_delete()
+                        ]
+                    },
+                    AppMode::Tunnels { .. } => {
+                        vec![
+                            "  ↑/↓/j/k: Navigate  │  r: Restart  │  x: Stop  │  Esc/T: Back"
+                        ]
+                    },
+                    AppMode::Backups { .. } => {
+                        vec![
+                            "  ↑/↓/j/k: Navigate  │  Enter: Restore (the current config is backed up first)  │  Esc/R: Back"
+                        ]
+                    },
+                    AppMode::Filter => {
+                        vec![
+                            "  Type to filter (tag:NAME, !tag:NAME)  │  ↑/↓: Navigate  │  Enter: Connect  │  Tab: Keep Filter  │  Esc: Clear"
+                        ]
+                    },
+                    AppMode::Form { kind: FormKind::Transfer(_), .. } => {
+                        vec![
+                            "  Direction: upload/download  │  Empty Remote: Home  │  Empty Local: Current Dir  │  Tab/↑/↓: Switch Field  │  Enter: Start  │  Esc: Cancel"
+                        ]
+                    },
+                    AppMode::Form { fields, selected, .. } => {
+                        let is_multiline = fields.get(*selected).map(|f| f.is_multiline).unwrap_or(false);
+                        if is_multiline {
+                            vec![
+                                "  ←/→: Move Cursor  │  ↑/↓: Move Line  │  Shift+Enter: New Line  │  Tab: Next  │  Enter: Save  │  Esc: Cancel"
+                            ]
+                        } else {
+                            vec![
+                                "  ←/→: Move Cursor  │  Home/End: Jump  │  Tab/↓: Next (Tab completes paths)  │  Shift+Tab/↑: Prev  │  Enter: Save  │  Esc: Cancel"
+                            ]
+                        }
+                    },
+                };
+                
+                let (help_text, help_color) = match &app.status {
+                    Some(status) => (vec![format!("  {}", status)], Color::Red),
+                    None => (help_text.iter().map(|line| line.to_string()).collect(), Color::Cyan),
+                };
+                // Grows to fit the help on narrow terminals instead of cutting it off.
+                let help_rows = help_text.iter()
+                    .map(|line| wrapped_rows(line, size.width.saturating_sub(2) as usize))
+                    .sum::<usize>()
+                    .clamp(1, 4);
                 let chunks = Layout::default()
                     .direction(Direction::Vertical)
-                    .constraints([Constraint::Min(1), Constraint::Length(3)].as_ref())
+                    .constraints([Constraint::Min(1), Constraint::Length(help_rows as u16 + 2)].as_ref())
                     .split(size);
 
                 let main_chunks = Layout::default()
@@ -181,22 +983,61 @@ impl Ui {
                     .constraints([Constraint::Percentage(45), Constraint::Percentage(55)].as_ref())
                     .split(chunks[0]);
 
-                let items: Vec<ListItem> = app.hosts.iter().enumerate().map(|(idx, h)| {
-                    let display = if !app.hosts.is_empty() && idx == app.list_index {
-                        format!("▶ {} @ {}", h.name, h.host)
-                    } else {
-                        format!("  {} @ {}", h.name, h.host)
-                    };
-                    ListItem::new(display)
-                        .style(if idx == app.list_index {
-                            Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD)
-                        } else {
-                            Style::default().fg(Color::White)
-                        })
+                let rows = app.rows();
+                let items: Vec<ListItem> = rows.iter().enumerate().map(|(row_idx, row)| {
+                    let is_selected = row_idx == app.cursor;
+                    let marker = if is_selected { "▶ " } else { "  " };
+                    match row {
+                        ListRow::Group { name, depth, count, collapsed, .. } => {
+                            let arrow = if *collapsed { "▸" } else { "▾" };
+                            let display = format!("{}{}{} {} ({})", marker, "  ".repeat(*depth), arrow, name, count);
+                            ListItem::new(display).style(if is_selected {
+                                Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD)
+                            } else {
+                                Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)
+                            })
+                        }
+                        ListRow::Host { idx, depth } => {
+                            let h = &app.hosts[*idx];
+                            let tunnel_mark = if tunnels.is_running(&h.name) { "  ⇄" } else { "" };
+                            let master_mark = if app.masters.contains(&h.name) { "  ◈" } else { "" };
+                            let style = if is_selected {
+                                Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD)
+                            } else {
+                                Style::default().fg(Color::White)
+                            };
+                            let (name_hits, host_hits) = match filter::match_host(*idx, h, &app.filter) {
+                                Some(m) => (m.name_positions, m.host_positions),
+                                None => (vec![], vec![]),
+                            };
+                            let status = health.status(h);
+                            let mut spans = vec![Span::raw(format!("{}{}", marker, "  ".repeat(*depth)))];
+                            if app.selection.contains(idx) {
+                                spans.push(Span::styled("✓ ", Style::default().fg(Color::Green).add_modifier(Modifier::BOLD)));
+                            }
+                            if health.is_enabled() {
+                                spans.push(health_dot(status.as_ref()));
+                                spans.push(Span::raw(" "));
+                            }
+                            spans.extend(highlight(&h.name, &name_hits));
+                            spans.push(Span::raw(" @ "));
+                            spans.extend(highlight(&h.host, &host_hits));
+                            if let Some(Ok(probe)) = status.as_ref().map(|s| &s.result) {
+                                spans.push(Span::styled(format!(" {}", health::format_latency(probe.latency)), Style::default().fg(Color::DarkGray)));
+                            }
+                            spans.push(Span::raw(tunnel_mark));
+                            spans.push(Span::raw(master_mark));
+                            if !h.tags.is_empty() {
+                                spans.push(Span::raw(" "));
+                                spans.extend(tag_chips(&h.tags, usize::MAX).0);
+                            }
+                            ListItem::new(Spans::from(spans)).style(style)
+                        }
+                    }
                 }).collect();
                 
                 let mut list_state = tui::widgets::ListState::default();
-                list_state.select(Some(app.list_index));
+                list_state.select(Some(app.cursor));
                 
                 let list = List::new(items)
                     .block(
@@ -204,7 +1045,26 @@ impl Ui {
                             .borders(Borders::ALL)
                             .border_style(Style::default().fg(Color::Cyan))
                             .title(Spans::from(vec![
-                                Span::styled("📡 SSH Hosts", Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD))
+                                Span::styled(
+                                    format!("{}{}{}",
+                                        if app.filter.is_empty() && !matches!(app.mode, AppMode::Filter) {
+                                            "📡 SSH Hosts".to_string()
+                                        } else {
+                                            format!("🔍 /{} ({}/{})", app.filter, rows.len(), app.hosts.len())
+                                        },
+                                        if app.sort == SortMode::Config {
+                                            String::new()
+                                        } else {
+                                            format!(" · by {}", app.sort.label())
+                                        },
+                                        if app.selection.is_empty() {
+                                            String::new()
+                                        } else {
+                                            format!(" · {} selected", app.selection.len())
+                                        }
+                                    ),
+                                    Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD)
+                                )
                             ]))
                     )
                     .highlight_style(
@@ -217,17 +1077,46 @@ impl Ui {
                 f.render_stateful_widget(list, main_chunks[0], &mut list_state);
 
                 match &app.mode {
-                    AppMode::Normal => {
+                    AppMode::Normal | AppMode::Filter => {
                         if let Some(h) = app.selected_host() {
                             let info_lines = [
-                                format!("┌─ Host Information ──────────────────────┐"),
+                                "┌─ Host Information ──────────────────────┐".to_string(),
                                 format!("│ Name:    {:40} │", truncate(&h.name, 40)),
+                                format!("│ Group:   {:40} │", truncate(&match h.group_path() {
+                                    path if path.is_empty() => UNGROUPED.to_string(),
+                                    path => path.join("/"),
+                                }, 40)),
                                 format!("│ User:    {:40} │", truncate(&h.user, 40)),
                                 format!("│ Host:    {:40} │", truncate(&h.host, 40)),
                                 format!("│ Port:    {:40} │", h.port.unwrap_or(22).to_string()),
+                                format!("│ Identity: {:39} │",
+                                    match &h.identity_file {
+                                        Some(path) if h.identities_only => truncate(&format!("{} (only)", path), 39),
+                                        Some(path) => truncate(path, 39),
+                                        None => "(default)".to_string(),
+                                    }
+                                ),
+                                format!("│ Route:   {:40} │",
+                                    truncate(&match config::jump_route(&app.hosts, h) {
+                                        Ok(route) if route.is_empty() => "(direct)".to_string(),
+                                        Ok(route) => route.iter()
+                                            .map(|hop| hop.label())
+                                            .chain(std::iter::once(h.name.as_str()))
+                                            .collect::<Vec<_>>()
+                                            .join(" → "),
+                                        Err(e) => format!("⚠ {}", e),
+                                    }, 40)
+                                ),
+                                format!("│ Forward: {:40} │",
+                                    truncate(&if h.forwards.is_empty() {
+                                        "(none)".to_string()
+                                    } else {
+                                        h.forwards.iter().map(|f| f.to_string()).collect::<Vec<_>>().join(", ")
+                                    }, 40)
+                                ),
                                 format!("│ Password: {:39} │", 
                                     if h.password.is_some() {
-                                        truncate(h.password.as_ref().unwrap(), 39)
+                                        "********".to_string()
                                     } else {
                                         "(not set)".to_string()
                                     }
@@ -235,11 +1124,36 @@ impl Ui {
                                 format!("│ Command: {:40} │", 
                                     truncate(&h.command.clone().unwrap_or_else(|| "(none)".to_string()), 40)
                                 ),
-                                format!("└──────────────────────────────────────────┘"),
+                                "└──────────────────────────────────────────┘".to_string(),
                             ]
                             .to_vec();
-                            
-                            let info_widget = Paragraph::new(info_lines.join("\n"))
+
+                            let mut info_text: Vec<Spans> = info_lines.into_iter().map(Spans::from).collect();
+                            let mut tags_line = vec![Span::raw("│ Tags:    ")];
+                            let width = if h.tags.is_empty() {
+                                tags_line.push(Span::raw("(none)"));
+                                "(none)".len()
+                            } else {
+                                let (chips, width) = tag_chips(&h.tags, 40);
+                                tags_line.extend(chips);
+                                width
+                            };
+                            tags_line.push(Span::raw(format!("{} │", " ".repeat(40 - width))));
+                            if health.is_enabled() {
+                                let status = health.status(h);
+                                let text = truncate(&health_text(h, status.as_ref()), 38);
+                                info_text.insert(6, Spans::from(vec![
+                                    Span::raw("│ Status:  "),
+                                    health_dot(status.as_ref()),
+                                    Span::raw(format!(" {:38} │", text)),
+                                ]));
+                            }
+                            info_text.insert(3, Spans::from(tags_line));
+                            let last = info_text.len() - 1;
+                            info_text.insert(last, Spans::from(format!("│ History: {:40} │",
+                                truncate(&history_text(app.stats.get(&h.name)), 40))));
+
+                            let info_widget = Paragraph::new(info_text)
                                 .style(Style::default().fg(Color::Green))
                                 .block(
                                     Block::default()
@@ -250,6 +1164,27 @@ impl Ui {
                                         ]))
                                 );
                         f.render_widget(info_widget, main_chunks[1]);
+                        } else if let Some(ListRow::Group { path, name, count, collapsed, .. }) = rows.get(app.cursor) {
+                            let group_lines = [
+                                "┌─ Group ──────────────────────────────────┐".to_string(),
+                                format!("│ Path:    {:40} │", truncate(if path == UNGROUPED_PATH { name } else { path }, 40)),
+                                format!("│ Hosts:   {:40} │", count),
+                                format!("│ State:   {:40} │", if *collapsed { "collapsed" } else { "expanded" }),
+                                "│                                          │".to_string(),
+                                "│ Enter/→/l: expand   ←/h: collapse        │".to_string(),
+                                "└──────────────────────────────────────────┘".to_string(),
+                            ];
+                            let group_widget = Paragraph::new(group_lines.join("\n"))
+                                .style(Style::default().fg(Color::Yellow))
+                                .block(
+                                    Block::default()
+                                        .borders(Borders::ALL)
+                                        .border_style(Style::default().fg(Color::Yellow))
+                                        .title(Spans::from(vec![
+                                            Span::styled("📁 Group Details", Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD))
+                                        ]))
+                                );
+                            f.render_widget(group_widget, main_chunks[1]);
                         } else {
                             let empty_msg = [
                                 "┌──────────────────────────────────────────┐",
@@ -273,11 +1208,11 @@ impl Ui {
                             f.render_widget(empty_widget, main_chunks[1]);
                         }
                     }
-                    AppMode::Form { fields, selected, editing_host_idx } => {
-                        let title = if editing_host_idx.is_some() {
-                            "✏️  Edit Host"
-                        } else {
-                            "➕ Add New Host"
+                    AppMode::Form { fields, selected, kind, suggestions } => {
+                        let title = match kind {
+                            FormKind::Add => "➕ Add New Host",
+                            FormKind::Edit(_) => "✏️  Edit Host",
+                            FormKind::Transfer(_) => "⇅ File Transfer",
                         };
                         
                         let mut form_lines = vec![format!("┌─ {} ────────────────────────────────┐", title)];
@@ -368,6 +1303,12 @@ impl Ui {
                                 };
                                 
                                 form_lines.push(format!("{}{:38} │", label, display_value));
+
+                                if is_selected {
+                                    for suggestion in suggestions.iter().take(4) {
+                                        form_lines.push(format!("│    ↳ {:37} │", truncate(suggestion, 37)));
+                                    }
+                                }
                             }
                             
                             if is_selected && i < fields.len() - 1 {
@@ -393,17 +1334,28 @@ impl Ui {
                             );
                         f.render_widget(form_widget, main_chunks[1]);
                     }
-                    AppMode::ConfirmDelete { host_name, .. } => {
-                        let host_name_display = i18n_ref.confirm_delete_host(&truncate(host_name, 30));
-                        let confirm_lines = vec![
-                            "┌──────────────────────────────────────────┐",
-                            "│                                          │",
-                            host_name_display.as_str(),
-                            "│                                          │",
-                            i18n_ref.press_y_to_confirm(),
-                            "│                                          │",
-                            "└──────────────────────────────────────────┘",
+                    AppMode::ConfirmDelete { host_names, .. } => {
+                        let mut confirm_lines = vec![
+                            "┌──────────────────────────────────────────┐".to_string(),
+                            "│                                          │".to_string(),
                         ];
+                        if let [host_name] = host_names.as_slice() {
+                            confirm_lines.push(i18n_ref.confirm_delete_host(&truncate(host_name, 30)));
+                        } else {
+                            confirm_lines.push(i18n_ref.confirm_delete_hosts(host_names.len()));
+                            for name in host_names.iter().take(8) {
+                                confirm_lines.push(format!("│    • {:35} │", truncate(name, 35)));
+                            }
+                            if host_names.len() > 8 {
+                                confirm_lines.push(format!("│    {:37} │", format!("… +{}", host_names.len() - 8)));
+                            }
+                        }
+                        confirm_lines.extend([
+                            "│                                          │".to_string(),
+                            i18n_ref.press_y_to_confirm().to_string(),
+                            "│                                          │".to_string(),
+                            "└──────────────────────────────────────────┘".to_string(),
+                        ]);
                         
                         let confirm_widget = Paragraph::new(confirm_lines.join("\n"))
                             .style(Style::default().fg(Color::Red))
@@ -417,35 +1369,82 @@ impl Ui {
                             );
                         f.render_widget(confirm_widget, main_chunks[1]);
                     }
+                    AppMode::Tunnels { selected } => {
+                        let running = tunnels.tunnels().iter().filter(|t| t.is_running()).count();
+                        let title = format!("⇄ Tunnels ({}/{} up)", running, tunnels.tunnels().len());
+                        let tunnel_list = List::new(Self::tunnel_items(tunnels.tunnels(), *selected))
+                            .block(
+                                Block::default()
+                                    .borders(Borders::ALL)
+                                    .border_style(Style::default().fg(Color::Magenta))
+                                    .title(Spans::from(vec![
+                                        Span::styled(title, Style::default().fg(Color::Magenta).add_modifier(Modifier::BOLD))
+                                    ]))
+                            );
+                        f.render_widget(tunnel_list, main_chunks[1]);
+                    }
+                    AppMode::Backups { backups, selected, preview } => {
+                        let chunks = Layout::default()
+                            .direction(Direction::Vertical)
+                            .constraints([Constraint::Length(backups.len().clamp(1, 8) as u16 + 2), Constraint::Min(3)].as_ref())
+                            .split(main_chunks[1]);
+                        let items: Vec<ListItem> = if backups.is_empty() {
+                            vec![ListItem::new("  (no backups yet)").style(Style::default().fg(Color::DarkGray))]
+                        } else {
+                            backups.iter().enumerate().map(|(idx, b)| {
+                                let marker = if idx == *selected { "▶" } else { " " };
+                                let style = if idx == *selected {
+                                    Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD)
+                                } else {
+                                    Style::default().fg(Color::White)
+                                };
+                                ListItem::new(format!("{} {} UTC", marker, b.created)).style(style)
+                            }).collect()
+                        };
+                        let mut list_state = tui::widgets::ListState::default();
+                        list_state.select(Some(*selected));
+                        let backup_list = List::new(items)
+                            .block(
+                                Block::default()
+                                    .borders(Borders::ALL)
+                                    .border_style(Style::default().fg(Color::Magenta))
+                                    .title(Spans::from(vec![
+                                        Span::styled(format!("⟲ Backups ({})", backups.len()), Style::default().fg(Color::Magenta).add_modifier(Modifier::BOLD))
+                                    ]))
+                            );
+                        f.render_stateful_widget(backup_list, chunks[0], &mut list_state);
+                        let diff_list = List::new(Self::diff_items(preview))
+                            .block(
+                                Block::default()
+                                    .borders(Borders::ALL)
+                                    .border_style(Style::default().fg(Color::Magenta))
+                                    .title(Spans::from(vec![
+                                        Span::styled("Changes on restore", Style::default().fg(Color::Magenta).add_modifier(Modifier::BOLD))
+                                    ]))
+                            );
+                        f.render_widget(diff_list, chunks[1]);
+                    }
+                    AppMode::Help => {
+                        let lines: Vec<String> = NORMAL_KEYS.iter()
+                            .map(|(key, action)| format!(" {:10} {}", key, action))
+                            .collect();
+                        let keys = Paragraph::new(lines.join("\n"))
+                            .style(Style::default().fg(Color::White))
+                            .block(
+                                Block::default()
+                                    .borders(Borders::ALL)
+                                    .border_style(Style::default().fg(Color::Cyan))
+                                    .title(Spans::from(vec![
+                                        Span::styled("⌨️  All Keys", Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD))
+                                    ]))
+                            );
+                        f.render_widget(keys, main_chunks[1]);
+                    }
                 }
 
-                let help_text = match &app.mode {
-                    AppMode::Normal => {
-                        vec![
-                            "  ↑/↓/j/k: Navigate  │  Enter: Connect  │  a: Add  │  e: Edit  │  d: Delete  │  y: Copy  │  p: Paste  │  q/Ctrl+C: Quit"
-                        ]
-                    },
-                    AppMode::ConfirmDelete { .. } => {
-                        vec![
-                            i18n_ref.confirm_delete()
-                        ]
-                    },
-                    AppMode::Form { fields, selected, .. } => {
-                        let is_multiline = fields.get(*selected).map(|f| f.is_multiline).unwrap_or(false);
-                        if is_multiline {
-                            vec![
-                                "  ←/→: Move Cursor  │  ↑/↓: Move Line  │  Shift+Enter: New Line  │  Enter: Save  │  Esc: Cancel"
-                            ]
-                        } else {
-                            vec![
-                                "  ←/→: Move Cursor  │  Home/End: Jump  │  Tab/↓: Next  │  Shift+Tab/↑: Prev  │  Enter: Save  │  Esc: Cancel"
-                            ]
-                        }
-                    },
-                };
-                
                 let help = Paragraph::new(help_text.join("\n"))
-                    .style(Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD))
+                    .style(Style::default().fg(help_color).add_modifier(Modifier::BOLD))
+                    .wrap(Wrap { trim: true })
                     .block(
                         Block::default()
                             .borders(Borders::ALL)
@@ -459,6 +1458,7 @@ impl Ui {
 
             if crossterm::event::poll(std::time::Duration::from_millis(100))? {
                 if let Event::Key(KeyEvent { code, modifiers, .. }) = event::read()? {
+                    app.status = None;
                     if code == KeyCode::Char('c') && (modifiers.contains(KeyModifiers::CONTROL) || modifiers.contains(KeyModifiers::SUPER)) {
                         Self::exit_tui(&mut terminal)?;
                         on_action(Action::Quit);
@@ -469,113 +1469,155 @@ impl Ui {
                         AppMode::Normal => match code {
                             KeyCode::Up | KeyCode::Char('k') => app.move_prev(),
                             KeyCode::Down | KeyCode::Char('j') => app.move_next(),
+                            KeyCode::Esc if !app.selection.is_empty() => app.clear_selection(),
+                            KeyCode::Esc if !app.filter.is_empty() => app.set_filter(""),
                             KeyCode::Char('q') | KeyCode::Esc => {
                                 Self::exit_tui(&mut terminal)?;
                                 on_action(Action::Quit);
                                 break;
                             }
+                            KeyCode::Char('/') => {
+                                app.mode = AppMode::Filter;
+                            }
                             KeyCode::Enter => {
                                 if let Some(h) = app.selected_host() {
                                     Self::exit_tui(&mut terminal)?;
                                     on_action(Action::Connect(h.clone()));
                                     break;
                                 }
+                                app.toggle_group();
+                            }
+                            KeyCode::Left | KeyCode::Char('h') => app.collapse(),
+                            KeyCode::Right | KeyCode::Char('l') => app.expand(),
+                            KeyCode::Char('t') => {
+                                if let Some(h) = app.selected_host().filter(|h| !h.forwards.is_empty()) {
+                                    Self::exit_tui(&mut terminal)?;
+                                    on_action(Action::Tunnel(h.clone()));
+                                    break;
+                                }
+                            }
+                            KeyCode::Char('b') => {
+                                if let Some(h) = app.selected_host().filter(|h| !h.forwards.is_empty()) {
+                                    tunnels.start(h, &app.hosts);
+                                    let selected = tunnels.tunnels().iter().position(|t| t.host.name == h.name).unwrap_or(0);
+                                    app.mode = AppMode::Tunnels { selected };
+                                }
+                            }
+                            KeyCode::Char('x') => {
+                                let targets: Vec<Host> = app.targets().into_iter().map(|idx| app.hosts[idx].clone()).collect();
+                                if !targets.is_empty() {
+                                    Self::exit_tui(&mut terminal)?;
+                                    on_action(Action::Exec(targets));
+                                    break;
+                                }
+                            }
+                            KeyCode::Char(' ') => app.toggle_selection(),
+                            KeyCode::Char('V') => app.select_range(),
+                            KeyCode::Char('*') => app.select_all_visible(),
+                            KeyCode::Char('T') => {
+                                app.mode = AppMode::Tunnels { selected: 0 };
                             }
                             KeyCode::Char('y') => {
-                                if let Some(h) = app.selected_host() {
-                                    let host_clone = h.clone();
-                                    app.clipboard = Some(host_clone.clone());
-                                    match clipboard::ClipboardContext::new() {
-                                        Ok(mut ctx) => {
-                                            let ssh_cmd = format!("ssh -p {} {}@{}", 
-                                                host_clone.port.unwrap_or(22), host_clone.user, host_clone.host);
-                                            let _ = ctx.set_contents(ssh_cmd);
-                                        }
-                                        Err(_) => {}
+                                let copied: Vec<Host> = app.targets().into_iter().map(|idx| app.hosts[idx].clone()).collect();
+                                if !copied.is_empty() {
+                                    if let Ok(mut ctx) = clipboard::ClipboardContext::new() {
+                                        let ssh_cmds: Vec<String> = copied.iter()
+                                            .map(|h| format!("ssh -p {} {}@{}", h.port.unwrap_or(22), h.user, h.host))
+                                            .collect();
+                                        let _ = ctx.set_contents(ssh_cmds.join("\n"));
                                     }
+                                    app.clipboard = copied;
                                     on_action(Action::Copy);
                                 }
                             }
                             KeyCode::Char('p') => {
-                                if let Some(clipped_host) = &app.clipboard {
-                                    let new_host = clipped_host.clone();
-                                    Self::validate_and_exit_on_error(&new_host, &mut terminal, &i18n)?;
-                                    Self::exit_tui(&mut terminal)?;
-                                    on_action(Action::Add(new_host));
-                                    break;
-                                } else {
-                                    match clipboard::ClipboardContext::new() {
-                                        Ok(mut ctx) => {
-                                            match ctx.get_contents() {
-                                                Ok(content) => {
-                                                    if let Some(parsed_host) = Self::parse_ssh_command(&content) {
-                                                        Self::validate_and_exit_on_error(&parsed_host, &mut terminal, &i18n)?;
-                                                        app.clipboard = Some(parsed_host.clone());
-                                                        Self::exit_tui(&mut terminal)?;
-                                                        on_action(Action::Add(parsed_host));
-                                                        break;
-                                                    } else {
-                                                        Self::exit_tui(&mut terminal)?;
-                                                        eprintln!("{}", i18n.clipboard_parse_error());
-                                                        std::process::exit(1);
-                                                    }
-                                                }
-                                                Err(_) => {}
-                                            }
+                                if !app.clipboard.is_empty() {
+                                    if app.clipboard.iter().all(Self::is_complete) {
+                                        Self::exit_tui(&mut terminal)?;
+                                        for new_host in &app.clipboard {
+                                            on_action(Action::Add(new_host.clone()));
+                                        }
+                                        break;
+                                    }
+                                    app.status = Some(i18n.invalid_host_format().to_string());
+                                } else if let Ok(content) = clipboard::ClipboardContext::new().and_then(|mut ctx| ctx.get_contents()) {
+                                    match Self::parse_ssh_command(&content) {
+                                        Some(parsed_host) if Self::is_complete(&parsed_host) => {
+                                            app.clipboard = vec![parsed_host.clone()];
+                                            Self::exit_tui(&mut terminal)?;
+                                            on_action(Action::Add(parsed_host));
+                                            break;
                                         }
-                                        Err(_) => {}
+                                        Some(_) => app.status = Some(i18n.invalid_host_format().to_string()),
+                                        None => app.status = Some(i18n.clipboard_parse_error().to_string()),
                                     }
                                 }
                             }
                             KeyCode::Char('a') => {
-                                let fields = vec![
-                                    FormField { label: "Name".into(), value: "".into(), cursor_pos: 0, is_multiline: false },
-                                    FormField { label: "User".into(), value: "".into(), cursor_pos: 0, is_multiline: false },
-                                    FormField { label: "Host".into(), value: "".into(), cursor_pos: 0, is_multiline: false },
-                                    FormField { label: "Port".into(), value: "22".into(), cursor_pos: 2, is_multiline: false },
-                                    FormField { label: "Password".into(), value: "".into(), cursor_pos: 0, is_multiline: false },
-                                    FormField { label: "Command".into(), value: "".into(), cursor_pos: 0, is_multiline: true },
-                                ];
-                                app.mode = AppMode::Form { fields, selected: 0, editing_host_idx: None };
+                                let fields = Self::form_fields(&Host { port: Some(22), ..Default::default() });
+                                app.mode = AppMode::Form { fields, selected: 0, kind: FormKind::Add, suggestions: vec![] };
                             }
                             KeyCode::Char('e') => {
-                                if let Some(idx) = app.hosts.get(app.list_index) {
-                                    let name_len = idx.name.len();
-                                    let user_len = idx.user.len();
-                                    let host_len = idx.host.len();
-                                    let port_str = idx.port.unwrap_or(22).to_string();
-                                    let port_len = port_str.len();
-                                    let password_str = idx.password.clone().unwrap_or_default();
-                                    let password_len = password_str.len();
-                                    let command_str = idx.command.clone().unwrap_or_default();
-                                    let command_len = command_str.len();
-                                    
-                                    let fields = vec![
-                                        FormField { label: "Name".into(), value: idx.name.clone(), cursor_pos: name_len, is_multiline: false },
-                                        FormField { label: "User".into(), value: idx.user.clone(), cursor_pos: user_len, is_multiline: false },
-                                        FormField { label: "Host".into(), value: idx.host.clone(), cursor_pos: host_len, is_multiline: false },
-                                        FormField { label: "Port".into(), value: port_str, cursor_pos: port_len, is_multiline: false },
-                                        FormField { label: "Password".into(), value: password_str, cursor_pos: password_len, is_multiline: false },
-                                        FormField { label: "Command".into(), value: command_str, cursor_pos: command_len, is_multiline: true },
-                                    ];
-                                    app.mode = AppMode::Form { fields, selected: 0, editing_host_idx: Some(app.list_index) };
+                                if let Some(idx) = app.selected_index() {
+                                    let fields = Self::form_fields(&app.hosts[idx]);
+                                    app.mode = AppMode::Form { fields, selected: 0, kind: FormKind::Edit(idx), suggestions: vec![] };
                                 }
                             }
-                            KeyCode::Char('d') => {
+                            KeyCode::Char('f') => {
+                                if let Some(idx) = app.selected_index() {
+                                    app.mode = AppMode::Form { fields: Self::transfer_fields(), selected: 0, kind: FormKind::Transfer(idx), suggestions: vec![] };
+                                }
+                            }
+                            KeyCode::Char('F') => {
                                 if let Some(h) = app.selected_host() {
-                                    let idx = app.list_index;
-                                    app.mode = AppMode::ConfirmDelete {
-                                        host_idx: idx,
-                                        host_name: h.name.clone(),
-                                    };
+                                    Self::exit_tui(&mut terminal)?;
+                                    on_action(Action::Browse(h.clone()));
+                                    break;
+                                }
+                            }
+                            KeyCode::Char('m') => {
+                                if let Some(h) = app.selected_host() {
+                                    Self::exit_tui(&mut terminal)?;
+                                    on_action(Action::CheckMaster(h.clone()));
+                                    break;
+                                }
+                            }
+                            KeyCode::Char('M') => {
+                                if let Some(h) = app.selected_host() {
+                                    Self::exit_tui(&mut terminal)?;
+                                    on_action(Action::CloseMaster(h.clone()));
+                                    break;
+                                }
+                            }
+                            KeyCode::Char('?') => app.mode = AppMode::Help,
+                            KeyCode::Char('R') => {
+                                let backups = backup::list(&backup::backups_dir()).unwrap_or_default();
+                                let preview = Self::backup_preview(backups.first());
+                                app.mode = AppMode::Backups { backups, selected: 0, preview };
+                            }
+                            KeyCode::Char('s') => {
+                                app.set_sort(app.sort.next());
+                                session.sort = app.sort;
+                            }
+                            KeyCode::Char('i') => {
+                                Self::exit_tui(&mut terminal)?;
+                                on_action(Action::Import);
+                                break;
+                            }
+                            KeyCode::Char('d') => {
+                                let host_indices = app.targets();
+                                if !host_indices.is_empty() {
+                                    let host_names = host_indices.iter().map(|&idx| app.hosts[idx].name.clone()).collect();
+                                    app.mode = AppMode::ConfirmDelete { host_indices, host_names };
                                 }
                             }
                             _ => {}
                         },
-                        AppMode::Form { fields, selected, editing_host_idx } => {
+                        AppMode::Form { fields, selected, kind, suggestions } => {
                             Self::normalize_cursor_pos(&mut fields[*selected]);
                             let field = &mut fields[*selected];
                             match code {
+                                KeyCode::Tab if Self::complete_field(field, suggestions) => {}
                                 KeyCode::Tab => {
                                     *selected = (*selected + 1) % fields.len();
                                     Self::normalize_cursor_pos(&mut fields[*selected]);
@@ -644,32 +1686,26 @@ impl Ui {
                                         if is_shift {
                                             field.value.insert(field.cursor_pos, '\n');
                                             field.cursor_pos += 1;
-                                        } else {
-                                            Self::save_form_and_exit(fields, *editing_host_idx, &mut terminal, &mut on_action)?;
+                                        } else if Self::submit_form(fields, *kind, &app.hosts, &mut app.status, i18n_ref, &mut terminal, &mut on_action)? {
                                             break;
                                         }
-                                    } else {
-                                        Self::save_form_and_exit(fields, *editing_host_idx, &mut terminal, &mut on_action)?;
+                                    } else if Self::submit_form(fields, *kind, &app.hosts, &mut app.status, i18n_ref, &mut terminal, &mut on_action)? {
                                         break;
                                     }
                                 }
                                 KeyCode::Esc => {
                                     app.mode = AppMode::Normal;
                                 }
-                                KeyCode::Backspace => {
-                                    if field.cursor_pos > 0 {
-                                        let new_pos = Self::move_cursor_left(&field.value, field.cursor_pos);
-                                        let char_len = field.cursor_pos - new_pos;
-                                        field.value.drain(new_pos..new_pos + char_len);
-                                        field.cursor_pos = new_pos;
-                                    }
+                                KeyCode::Backspace if field.cursor_pos > 0 => {
+                                    let new_pos = Self::move_cursor_left(&field.value, field.cursor_pos);
+                                    let char_len = field.cursor_pos - new_pos;
+                                    field.value.drain(new_pos..new_pos + char_len);
+                                    field.cursor_pos = new_pos;
                                 }
-                                KeyCode::Delete => {
-                                    if field.cursor_pos < field.value.len() {
-                                        let next_pos = Self::move_cursor_right(&field.value, field.cursor_pos);
-                                        let char_len = next_pos - field.cursor_pos;
-                                        field.value.drain(field.cursor_pos..field.cursor_pos + char_len);
-                                    }
+                                KeyCode::Delete if field.cursor_pos < field.value.len() => {
+                                    let next_pos = Self::move_cursor_right(&field.value, field.cursor_pos);
+                                    let char_len = next_pos - field.cursor_pos;
+                                    field.value.drain(field.cursor_pos..field.cursor_pos + char_len);
                                 }
                                 KeyCode::Char(c) => {
                                     let char_len = c.len_utf8();
@@ -679,11 +1715,11 @@ impl Ui {
                                 _ => {}
                             }
                         },
-                        AppMode::ConfirmDelete { host_idx, .. } => {
+                        AppMode::ConfirmDelete { host_indices, .. } => {
                             match code {
                                 KeyCode::Char('y') => {
                                     Self::exit_tui(&mut terminal)?;
-                                    on_action(Action::Delete(*host_idx));
+                                    on_action(Action::Delete(std::mem::take(host_indices)));
                                     break;
                                 }
                                 KeyCode::Char('n') | KeyCode::Esc => {
@@ -692,6 +1728,91 @@ impl Ui {
                                 _ => {}
                             }
                         }
+                        AppMode::Help => {
+                            if matches!(code, KeyCode::Esc | KeyCode::Char('?') | KeyCode::Char('q')) {
+                                app.mode = AppMode::Normal;
+                            }
+                        }
+                        AppMode::Filter => {
+                            match code {
+                                KeyCode::Enter => {
+                                    if let Some(h) = app.selected_host() {
+                                        Self::exit_tui(&mut terminal)?;
+                                        on_action(Action::Connect(h.clone()));
+                                        break;
+                                    }
+                                }
+                                KeyCode::Esc => {
+                                    app.set_filter("");
+                                    app.mode = AppMode::Normal;
+                                }
+                                KeyCode::Tab => {
+                                    app.mode = AppMode::Normal;
+                                }
+                                KeyCode::Up => app.move_prev(),
+                                KeyCode::Down => app.move_next(),
+                                KeyCode::Backspace => {
+                                    let mut query = app.filter.clone();
+                                    query.pop();
+                                    app.set_filter(&query);
+                                }
+                                KeyCode::Char(c) => {
+                                    let query = format!("{}{}", app.filter, c);
+                                    app.set_filter(&query);
+                                }
+                                _ => {}
+                            }
+                        }
+                        AppMode::Tunnels { selected } => {
+                            match code {
+                                KeyCode::Up | KeyCode::Char('k') => {
+                                    *selected = selected.saturating_sub(1);
+                                }
+                                KeyCode::Down | KeyCode::Char('j') if *selected + 1 < tunnels.tunnels().len() => {
+                                    *selected += 1;
+                                }
+                                KeyCode::Char('r') => {
+                                    tunnels.restart(*selected, &app.hosts);
+                                    tunnels.refresh();
+                                }
+                                KeyCode::Char('x') => {
+                                    tunnels.stop(*selected);
+                                    *selected = (*selected).min(tunnels.tunnels().len().saturating_sub(1));
+                                }
+                                KeyCode::Esc | KeyCode::Char('T') | KeyCode::Char('q') => {
+                                    app.mode = AppMode::Normal;
+                                }
+                                _ => {}
+                            }
+                        }
+                        AppMode::Backups { backups, selected, preview } => {
+                            match code {
+                                KeyCode::Up | KeyCode::Char('k') if *selected > 0 => {
+                                    *selected -= 1;
+                                    *preview = Self::backup_preview(backups.get(*selected));
+                                }
+                                KeyCode::Down | KeyCode::Char('j') if *selected + 1 < backups.len() => {
+                                    *selected += 1;
+                                    *preview = Self::backup_preview(backups.get(*selected));
+                                }
+                                KeyCode::Enter => {
+                                    if let Some(b) = backups.get(*selected) {
+                                        let path = b.path.clone();
+                                        Self::exit_tui(&mut terminal)?;
+                                        on_action(Action::Restore(path));
+                                        break;
+                                    }
+                                }
+                                KeyCode::Esc | KeyCode::Char('R') | KeyCode::Char('q') => {
+                                    app.mode = AppMode::Normal;
+                                }
+                                _ => {}
+                            }
+                        }
+                    }
+
+                    if let AppMode::Form { fields, selected, suggestions, .. } = &mut app.mode {
+                        *suggestions = Self::suggestions_for(&fields[*selected], &app.hosts);
                     }
                 }
             }
@@ -701,7 +1822,7 @@ impl Ui {
     }
 
     pub fn parse_ssh_command(cmd: &str) -> Option<Host> {
-        let parts: Vec<&str> = cmd.trim().split_whitespace().collect();
+        let parts: Vec<&str> = cmd.split_whitespace().collect();
         if parts.is_empty() || parts[0] != "ssh" {
             return None;
         }
@@ -737,6 +1858,7 @@ impl Ui {
                         port: Some(port),
                         password: None,
                         command: None,
+                        ..Default::default()
                     });
                 }
             }
@@ -746,11 +1868,118 @@ impl Ui {
     }
 }
 
+/// Green when the last probe connected, red when it failed, grey when the
+/// host hasn't been (or can't be) checked.
+fn health_dot(status: Option<&HealthStatus>) -> Span<'static> {
+    match status {
+        Some(s) if s.is_up() => Span::styled("●", Style::default().fg(Color::Green)),
+        Some(_) => Span::styled("●", Style::default().fg(Color::Red)),
+        None => Span::styled("○", Style::default().fg(Color::DarkGray)),
+    }
+}
+
+fn health_text(h: &Host, status: Option<&HealthStatus>) -> String {
+    let Some(status) = status else {
+        return if health::is_checkable(h) { "checking...".into() } else { "not checked (behind a jump host)".into() };
+    };
+    let mut parts = match &status.result {
+        Ok(probe) => vec!["up".to_string(), health::format_latency(probe.latency)],
+        Err(e) => vec!["down".to_string(), e.clone()],
+    };
+    match (&status.result, status.last_seen) {
+        (Ok(probe), _) => parts.extend(probe.banner.clone()),
+        (Err(_), Some(seen)) => parts.push(format!("seen {}", health::format_ago(seen.elapsed().unwrap_or_default()))),
+        (Err(_), None) => {}
+    }
+    parts.join(" · ")
+}
+
+/// e.g. `12× · last 2h ago (exit 0)`.
+fn history_text(stats: Option<&HostStats>) -> String {
+    let Some(stats) = stats.filter(|s| s.count > 0) else {
+        return "(never connected)".into();
+    };
+    let ago = health::format_ago(Duration::from_secs(history::now().saturating_sub(stats.last)));
+    match stats.last_exit {
+        Some(code) => format!("{}× · last {} (exit {})", stats.count, ago, code),
+        None => format!("{}× · last {}", stats.count, ago),
+    }
+}
+
+/// How many rows `text` takes when word-wrapped to `width` columns.
+pub fn wrapped_rows(text: &str, width: usize) -> usize {
+    let width = width.max(1);
+    let mut rows = 1;
+    let mut used = 0;
+    for word in text.split_whitespace() {
+        let len = word.chars().count();
+        if used > 0 && used + 1 + len > width {
+            rows += 1;
+            used = 0;
+        }
+        used = if used == 0 { len } else { used + 1 + len };
+        // Words longer than a row are broken up.
+        while used > width {
+            rows += 1;
+            used -= width;
+        }
+    }
+    rows
+}
+
+/// Picks a stable colour per tag name so a tag looks the same everywhere.
+fn tag_color(tag: &str) -> Color {
+    const PALETTE: [Color; 6] = [Color::Magenta, Color::Blue, Color::Green, Color::Yellow, Color::Red, Color::Cyan];
+    let hash = tag.to_lowercase().bytes().fold(0usize, |acc, b| acc.wrapping_mul(31).wrapping_add(b as usize));
+    PALETTE[hash % PALETTE.len()]
+}
+
+/// Renders tags as coloured chips, as many as fit in `max_width` columns.
+/// Returns the spans and the width they take up.
+fn tag_chips(tags: &[String], max_width: usize) -> (Vec<Span<'static>>, usize) {
+    let mut spans = Vec::new();
+    let mut width = 0;
+    for tag in tags {
+        let chip = format!(" {} ", tag);
+        let chip_width = chip.chars().count() + usize::from(!spans.is_empty());
+        if width + chip_width > max_width {
+            break;
+        }
+        if !spans.is_empty() {
+            spans.push(Span::raw(" "));
+        }
+        spans.push(Span::styled(chip, Style::default().fg(Color::Black).bg(tag_color(tag))));
+        width += chip_width;
+    }
+    (spans, width)
+}
+
+/// Splits `text` into spans with the chars at `positions` emphasised.
+fn highlight(text: &str, positions: &[usize]) -> Vec<Span<'static>> {
+    let hit = Style::default().fg(Color::Yellow).add_modifier(Modifier::UNDERLINED);
+    let mut spans = Vec::new();
+    let mut run = String::new();
+    let mut run_is_hit = false;
+    for (i, c) in text.chars().enumerate() {
+        let is_hit = positions.contains(&i);
+        if is_hit != run_is_hit && !run.is_empty() {
+            let chunk = std::mem::take(&mut run);
+            spans.push(if run_is_hit { Span::styled(chunk, hit) } else { Span::raw(chunk) });
+        }
+        run_is_hit = is_hit;
+        run.push(c);
+    }
+    if !run.is_empty() {
+        spans.push(if run_is_hit { Span::styled(run, hit) } else { Span::raw(run) });
+    }
+    spans
+}
+
 pub fn truncate(s: &str, max_len: usize) -> String {
-    if s.len() <= max_len {
+    if s.chars().count() <= max_len {
         s.to_string()
     } else {
-        format!("{}...", &s[..max_len.saturating_sub(3)])
+        format!("{}...", s.chars().take(max_len.saturating_sub(3)).collect::<String>())
     }
 }
 
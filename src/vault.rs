@@ -0,0 +1,197 @@
+use argon2::{Algorithm, Argon2, Params, Version};
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use chacha20poly1305::aead::{Aead, KeyInit};
+use chacha20poly1305::{XChaCha20Poly1305, XNonce};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use zeroize::Zeroizing;
+
+const ENCRYPTED_PREFIX: &str = "enc:v1:";
+const CHECK_PLAINTEXT: &str = "lazyssh-vault";
+const NONCE_LEN: usize = 24;
+const SALT_LEN: usize = 16;
+
+pub const DEFAULT_KDF_MEMORY_KIB: u32 = 19 * 1024;
+pub const DEFAULT_KDF_ITERATIONS: u32 = 2;
+pub const MIN_KDF_MEMORY_KIB: u32 = 8 * 1024;
+pub const MIN_KDF_ITERATIONS: u32 = Params::MIN_T_COST;
+
+#[derive(Debug)]
+pub enum VaultError {
+    WrongPassphrase,
+    InvalidParams(String),
+    Corrupted,
+    /// The passphrase was right, but the passwords of these hosts don't
+    /// decrypt with it. They are left encrypted.
+    CorruptedPasswords(Vec<String>),
+}
+
+impl fmt::Display for VaultError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            VaultError::WrongPassphrase => write!(f, "wrong passphrase"),
+            VaultError::InvalidParams(e) => write!(f, "invalid key derivation parameters: {}", e),
+            VaultError::Corrupted => write!(f, "encrypted data is corrupted"),
+            VaultError::CorruptedPasswords(hosts) => {
+                write!(f, "the stored password of {} doesn't decrypt with this passphrase and was left as it is", hosts.join(", "))
+            }
+        }
+    }
+}
+
+impl std::error::Error for VaultError {}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KdfParams {
+    pub memory_kib: u32,
+    pub iterations: u32,
+}
+
+impl Default for KdfParams {
+    fn default() -> Self {
+        Self { memory_kib: DEFAULT_KDF_MEMORY_KIB, iterations: DEFAULT_KDF_ITERATIONS }
+    }
+}
+
+impl KdfParams {
+    /// These parameters, raised to the minimum a new vault accepts. Vaults
+    /// created before the minimum existed may be below it.
+    pub fn at_least_minimum(self) -> Self {
+        Self {
+            memory_kib: self.memory_kib.max(MIN_KDF_MEMORY_KIB),
+            iterations: self.iterations.max(MIN_KDF_ITERATIONS),
+        }
+    }
+}
+
+/// Stored in `config.toml` under `[vault]`. Holds everything needed to
+/// re-derive the key from the passphrase, plus a check value to detect
+/// a wrong passphrase before any password is touched.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Vault {
+    pub kdf: String,
+    pub memory_kib: u32,
+    pub iterations: u32,
+    pub parallelism: u32,
+    pub salt: String,
+    pub check: String,
+    #[serde(skip)]
+    key: Option<VaultKey>,
+}
+
+/// Wiped from memory when dropped.
+#[derive(Clone)]
+struct VaultKey(Zeroizing<[u8; 32]>);
+
+impl fmt::Debug for VaultKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("VaultKey(..)")
+    }
+}
+
+impl Vault {
+    /// Creates a new vault for `passphrase`. The returned vault is unlocked.
+    pub fn create(passphrase: &str, params: KdfParams) -> Result<Self, VaultError> {
+        if params.memory_kib < MIN_KDF_MEMORY_KIB || params.iterations < MIN_KDF_ITERATIONS {
+            return Err(VaultError::InvalidParams(format!(
+                "need at least {} KiB of memory and {} iteration(s)",
+                MIN_KDF_MEMORY_KIB, MIN_KDF_ITERATIONS
+            )));
+        }
+        let mut salt = [0u8; SALT_LEN];
+        getrandom::getrandom(&mut salt).map_err(|e| VaultError::InvalidParams(e.to_string()))?;
+        let mut vault = Vault {
+            kdf: "argon2id".into(),
+            memory_kib: params.memory_kib,
+            iterations: params.iterations,
+            parallelism: 1,
+            salt: BASE64.encode(salt),
+            check: String::new(),
+            key: None,
+        };
+        let key = vault.derive_key(passphrase)?;
+        vault.check = key.encrypt(CHECK_PLAINTEXT);
+        vault.key = Some(key);
+        Ok(vault)
+    }
+
+    pub fn unlock(&mut self, passphrase: &str) -> Result<(), VaultError> {
+        let key = self.derive_key(passphrase)?;
+        match key.decrypt(&self.check) {
+            Ok(check) if check == CHECK_PLAINTEXT => {
+                self.key = Some(key);
+                Ok(())
+            }
+            _ => Err(VaultError::WrongPassphrase),
+        }
+    }
+
+    pub fn is_unlocked(&self) -> bool {
+        self.key.is_some()
+    }
+
+    pub fn params(&self) -> KdfParams {
+        KdfParams { memory_kib: self.memory_kib, iterations: self.iterations }
+    }
+
+    /// Returns `None` while the vault is locked.
+    pub fn encrypt(&self, plaintext: &str) -> Option<String> {
+        self.key.as_ref().map(|key| key.encrypt(plaintext))
+    }
+
+    pub fn decrypt(&self, value: &str) -> Result<String, VaultError> {
+        match &self.key {
+            Some(key) => key.decrypt(value),
+            None => Err(VaultError::WrongPassphrase),
+        }
+    }
+
+    fn derive_key(&self, passphrase: &str) -> Result<VaultKey, VaultError> {
+        if self.kdf != "argon2id" {
+            return Err(VaultError::InvalidParams(format!("unsupported kdf '{}'", self.kdf)));
+        }
+        let salt = BASE64.decode(&self.salt).map_err(|_| VaultError::Corrupted)?;
+        let params = Params::new(self.memory_kib, self.iterations, self.parallelism, Some(32))
+            .map_err(|e| VaultError::InvalidParams(e.to_string()))?;
+        let mut key = Zeroizing::new([0u8; 32]);
+        Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+            .hash_password_into(passphrase.as_bytes(), &salt, key.as_mut())
+            .map_err(|e| VaultError::InvalidParams(e.to_string()))?;
+        Ok(VaultKey(key))
+    }
+}
+
+impl VaultKey {
+    fn encrypt(&self, plaintext: &str) -> String {
+        let mut nonce = [0u8; NONCE_LEN];
+        getrandom::getrandom(&mut nonce).expect("system random number generator unavailable");
+        let cipher = XChaCha20Poly1305::new(self.0.as_ref().into());
+        let ciphertext = cipher
+            .encrypt(XNonce::from_slice(&nonce), plaintext.as_bytes())
+            .expect("encryption with a valid key cannot fail");
+        let mut blob = nonce.to_vec();
+        blob.extend_from_slice(&ciphertext);
+        format!("{}{}", ENCRYPTED_PREFIX, BASE64.encode(blob))
+    }
+
+    fn decrypt(&self, value: &str) -> Result<String, VaultError> {
+        let encoded = value.strip_prefix(ENCRYPTED_PREFIX).ok_or(VaultError::Corrupted)?;
+        let blob = BASE64.decode(encoded).map_err(|_| VaultError::Corrupted)?;
+        if blob.len() < NONCE_LEN {
+            return Err(VaultError::Corrupted);
+        }
+        let (nonce, ciphertext) = blob.split_at(NONCE_LEN);
+        let cipher = XChaCha20Poly1305::new(self.0.as_ref().into());
+        let plaintext = cipher
+            .decrypt(XNonce::from_slice(nonce), ciphertext)
+            .map_err(|_| VaultError::Corrupted)?;
+        String::from_utf8(plaintext).map_err(|_| VaultError::Corrupted)
+    }
+}
+
+/// Whether `value` is stored encrypted. Passwords starting with the prefix
+/// are refused when entered, so plaintext is never mistaken for ciphertext.
+pub fn is_encrypted(value: &str) -> bool {
+    value.starts_with(ENCRYPTED_PREFIX)
+}
@@ -0,0 +1,85 @@
+use lazyssh::backup::{create, diff, list, restore, write_atomic, DiffLine};
+use std::fs;
+use std::path::PathBuf;
+
+fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("lazyssh-{}-{}", name, std::process::id()));
+    fs::remove_dir_all(&dir).ok();
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+#[test]
+fn test_write_atomic() {
+    let dir = temp_dir("atomic");
+    let path = dir.join("config.toml");
+    write_atomic(&path, b"hosts = []\n").unwrap();
+    write_atomic(&path, b"[[hosts]]\nname = \"web\"\n").unwrap();
+    assert_eq!(fs::read_to_string(&path).unwrap(), "[[hosts]]\nname = \"web\"\n");
+    // Only the file itself is left, no temporary files.
+    assert_eq!(fs::read_dir(&dir).unwrap().count(), 1);
+    fs::remove_dir_all(&dir).ok();
+}
+
+#[test]
+fn test_create_and_prune() {
+    let dir = temp_dir("backups");
+    let path = dir.join("config.toml");
+    let backups = dir.join("backups");
+
+    assert_eq!(create(&path, &backups, 3).unwrap(), None, "no file yet");
+    for n in 1..=5 {
+        fs::write(&path, format!("# version {}\n", n)).unwrap();
+        assert!(create(&path, &backups, 3).unwrap().is_some());
+    }
+    // Unchanged since the newest backup, so nothing new.
+    assert_eq!(create(&path, &backups, 3).unwrap(), None);
+    assert_eq!(create(&path, &backups, 0).unwrap(), None);
+
+    let kept = list(&backups).unwrap();
+    let contents: Vec<String> = kept.iter().map(|b| fs::read_to_string(&b.path).unwrap()).collect();
+    assert_eq!(contents, vec!["# version 5\n", "# version 4\n", "# version 3\n"]);
+    assert!(kept[0].name().starts_with("config-"));
+    assert_eq!(kept[0].created.len(), "2026-10-18 15:30:00".len());
+
+    fs::remove_dir_all(&dir).ok();
+}
+
+#[test]
+fn test_restore() {
+    let dir = temp_dir("restore");
+    let path = dir.join("config.toml");
+    let backups = dir.join("backups");
+    let old = dir.join("old.toml");
+    fs::write(&old, "[[hosts]]\nname = \"web\"\nuser = \"u\"\nhost = \"h\"\n").unwrap();
+    fs::write(&path, "hosts = [oops\n").unwrap();
+
+    restore(&old, &path, &backups, 10).unwrap();
+    assert_eq!(fs::read_to_string(&path).unwrap(), fs::read_to_string(&old).unwrap());
+    // The broken file it replaced was kept.
+    let kept = list(&backups).unwrap();
+    assert_eq!(fs::read_to_string(&kept[0].path).unwrap(), "hosts = [oops\n");
+
+    // A backup that doesn't load isn't restored.
+    assert!(restore(&kept[0].path, &path, &backups, 10).is_err());
+    assert_eq!(fs::read_to_string(&path).unwrap(), fs::read_to_string(&old).unwrap());
+
+    fs::remove_dir_all(&dir).ok();
+}
+
+#[test]
+fn test_diff() {
+    let old = "a\nb\nc\nd\ne\nf\ng\nh\n";
+    let new = "a\nb\nc\nd\nE\nf\ng\nh\ni\n";
+    assert_eq!(diff(old, new, 1), vec![
+        DiffLine::Skipped(3),
+        DiffLine::Same("d".into()),
+        DiffLine::Removed("e".into()),
+        DiffLine::Added("E".into()),
+        DiffLine::Same("f".into()),
+        DiffLine::Skipped(1),
+        DiffLine::Same("h".into()),
+        DiffLine::Added("i".into()),
+    ]);
+    assert_eq!(diff(old, old, 2), vec![DiffLine::Skipped(8)]);
+}
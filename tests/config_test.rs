@@ -1,8 +1,8 @@
-use lazyssh::config::{Config, Host};
+use lazyssh::config::{Config, Host, LoadError, SaveError};
 
 #[test]
 fn test_add_host() {
-    let mut config = Config { hosts: vec![] };
+    let mut config = Config::default();
     let host = Host {
         name: "test".into(),
         user: "user".into(),
@@ -10,6 +10,7 @@ fn test_add_host() {
         port: Some(22),
         password: None,
         command: None,
+        ..Default::default()
     };
     config.add_host(host);
     assert_eq!(config.hosts.len(), 1);
@@ -27,6 +28,7 @@ fn test_remove_host() {
                 port: Some(22),
                 password: None,
                 command: None,
+                ..Default::default()
             },
             Host {
                 name: "test2".into(),
@@ -35,8 +37,10 @@ fn test_remove_host() {
                 port: Some(22),
                 password: None,
                 command: None,
+                ..Default::default()
             },
         ],
+        ..Default::default()
     };
     config.remove_host(0);
     assert_eq!(config.hosts.len(), 1);
@@ -57,8 +61,10 @@ fn test_update_host() {
                 port: Some(22),
                 password: None,
                 command: None,
+                ..Default::default()
             },
         ],
+        ..Default::default()
     };
     let updated_host = Host {
         name: "updated".into(),
@@ -67,6 +73,7 @@ fn test_update_host() {
         port: Some(2222),
         password: None,
         command: None,
+        ..Default::default()
     };
     config.update_host(0, updated_host);
     assert_eq!(config.hosts[0].name, "updated");
@@ -80,8 +87,308 @@ fn test_update_host() {
         port: Some(22),
         password: None,
         command: None,
+        ..Default::default()
     };
     config.update_host(10, new_host);
     assert_eq!(config.hosts.len(), 1);
 }
 
+
+#[test]
+fn test_import_hosts_reports_duplicates() {
+    let mut config = Config {
+        hosts: vec![Host {
+            name: "web".into(),
+            user: "deploy".into(),
+            host: "10.0.0.1".into(),
+            ..Default::default()
+        }],
+        ..Default::default()
+    };
+    let imported = vec![
+        Host { name: "web".into(), user: "root".into(), host: "10.0.0.9".into(), ..Default::default() },
+        Host { name: "db".into(), user: "root".into(), host: "10.0.0.2".into(), ..Default::default() },
+    ];
+    let report = config.import_hosts(imported);
+    assert_eq!(report.added, vec!["db".to_string()]);
+    assert_eq!(report.duplicates, vec!["web".to_string()]);
+    assert_eq!(config.hosts.len(), 2);
+    assert_eq!(config.hosts[0].host, "10.0.0.1");
+}
+
+fn jump_host(name: &str, user: &str, host: &str, port: Option<u16>, jump: Option<&str>) -> Host {
+    Host {
+        name: name.into(),
+        user: user.into(),
+        host: host.into(),
+        port,
+        jump: jump.map(String::from),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_jump_route_resolves_names_and_chains() {
+    let config = Config {
+        hosts: vec![
+            jump_host("edge", "ops", "edge.example.com", Some(22), None),
+            jump_host("bastion", "admin", "10.0.0.1", Some(2222), Some("edge")),
+            jump_host("db", "root", "10.0.1.5", None, Some("bastion, relay@198.51.100.7:22")),
+        ],
+        ..Default::default()
+    };
+    let route = lazyssh::config::jump_route(&config.hosts, &config.hosts[2]).unwrap();
+    let labels: Vec<&str> = route.iter().map(|hop| hop.label()).collect();
+    assert_eq!(labels, vec!["edge", "bastion", "relay@198.51.100.7:22"]);
+
+    let resolved = config.resolve_jump(&config.hosts[2]).unwrap();
+    assert_eq!(
+        resolved.jump.as_deref(),
+        Some("ops@edge.example.com:22,admin@10.0.0.1:2222,relay@198.51.100.7:22")
+    );
+    assert_eq!(config.resolve_jump(&config.hosts[0]).unwrap().jump, None);
+}
+
+#[test]
+fn test_jump_route_detects_cycles() {
+    use lazyssh::config::JumpError;
+
+    let config = Config {
+        hosts: vec![
+            jump_host("a", "u", "a.example.com", None, Some("b")),
+            jump_host("b", "u", "b.example.com", None, Some("a")),
+            jump_host("self", "u", "s.example.com", None, Some("self")),
+        ],
+        ..Default::default()
+    };
+    assert_eq!(
+        config.resolve_jump(&config.hosts[0]).unwrap_err(),
+        JumpError::Cycle(vec!["a".into(), "b".into(), "a".into()])
+    );
+    assert!(config.resolve_jump(&config.hosts[2]).is_err());
+}
+
+#[test]
+fn test_jump_hops_keep_their_credentials() {
+    use lazyssh::config::JumpError;
+
+    let mut config = Config {
+        hosts: vec![
+            jump_host("v6", "ops", "2001:db8::1", Some(2222), None),
+            jump_host("db", "root", "10.0.1.5", None, Some("v6")),
+        ],
+        ..Default::default()
+    };
+    assert_eq!(config.resolve_jump(&config.hosts[1]).unwrap().jump.as_deref(), Some("ops@[2001:db8::1]:2222"));
+
+    config.hosts[0].password = Some("hunter2".into());
+    assert_eq!(config.resolve_jump(&config.hosts[1]).unwrap_err(), JumpError::HopPassword("v6".into()));
+    config.hosts[0].identity_file = Some("~/.ssh/id_v6".into());
+    assert_eq!(config.resolve_jump(&config.hosts[1]).unwrap_err(), JumpError::HopIdentity("v6".into()));
+}
+
+#[test]
+fn test_forward_parse_and_display() {
+    use lazyssh::config::{Forward, ForwardKind};
+
+    let local = Forward::parse("L 5432:db:5432").unwrap();
+    assert_eq!(local.kind, ForwardKind::Local);
+    assert_eq!(local.listen, "5432");
+    assert_eq!(local.target.as_deref(), Some("db:5432"));
+    assert_eq!(local.to_string(), "L 5432:db:5432");
+
+    let remote = Forward::parse("-R 127.0.0.1:8080:localhost:80").unwrap();
+    assert_eq!(remote.kind, ForwardKind::Remote);
+    assert_eq!(remote.listen, "127.0.0.1:8080");
+    assert_eq!(remote.flag(), "-R");
+    assert_eq!(remote.spec(), "127.0.0.1:8080:localhost:80");
+
+    let socks = Forward::parse("d 1080").unwrap();
+    assert_eq!(socks.kind, ForwardKind::Dynamic);
+    assert_eq!(socks.target, None);
+    assert_eq!(socks.to_string(), "D 1080");
+
+    assert!(Forward::parse("L 5432").is_none());
+    assert!(Forward::parse("X 1:a:2").is_none());
+    assert!(Forward::parse("L 5432::5432").is_none());
+    assert!(Forward::parse("").is_none());
+}
+
+#[test]
+fn test_group_path() {
+    let mut h = Host { name: "web".into(), ..Default::default() };
+    assert!(h.group_path().is_empty());
+
+    h.group = Some(" prod / eu //web/".into());
+    assert_eq!(h.group_path(), vec!["prod", "eu", "web"]);
+
+    let cfg: Config = toml::from_str("[[hosts]]\nname = \"old\"\nuser = \"u\"\nhost = \"h\"\n").unwrap();
+    assert_eq!(cfg.hosts[0].group, None);
+}
+
+#[test]
+fn test_tags_roundtrip_through_toml() {
+    let mut cfg = Config::default();
+    cfg.add_host(Host {
+        name: "pg".into(),
+        tags: lazyssh::config::parse_tags("db, legacy"),
+        forwards: vec![lazyssh::config::Forward::parse("L 5432:db:5432").unwrap()],
+        ..Default::default()
+    });
+    let text = cfg.to_toml().unwrap();
+    let back: Config = toml::from_str(&text).unwrap();
+    assert_eq!(back.hosts[0].tags, vec!["db", "legacy"]);
+    assert!(back.hosts[0].has_tag("DB"));
+    assert_eq!(back.hosts[0].forwards.len(), 1);
+}
+
+#[test]
+fn test_host_index() {
+    let mut cfg = Config::default();
+    cfg.add_host(Host { name: "web".into(), ..Default::default() });
+    cfg.add_host(Host { name: "db".into(), ..Default::default() });
+    assert_eq!(cfg.host_index("db"), Some(1));
+    assert_eq!(cfg.host_index("DB"), None);
+}
+
+#[test]
+fn test_health_check_settings() {
+    let cfg = Config::default();
+    assert_eq!(cfg.health_check.interval, 0);
+    assert!(!cfg.to_toml().unwrap().contains("health_check"));
+
+    let cfg: Config = toml::from_str("hosts = []\n\n[health_check]\ninterval = 60\n").unwrap();
+    assert_eq!(cfg.health_check.interval, 60);
+    assert_eq!(cfg.health_check.timeout, 3);
+    assert!(!cfg.health_check.read_banner);
+    assert!(cfg.to_toml().unwrap().contains("[health_check]"));
+}
+
+#[test]
+fn test_multiplex_persist() {
+    let own = Host { name: "web".into(), control_persist: Some("1h".into()), ..Default::default() };
+    let plain = Host { name: "db".into(), ..Default::default() };
+
+    let cfg = Config::default();
+    assert_eq!(cfg.multiplex.persist_for(&own), Some("1h"));
+    assert_eq!(cfg.multiplex.persist_for(&plain), None);
+    assert!(!cfg.to_toml().unwrap().contains("multiplex"));
+
+    let cfg: Config = toml::from_str("hosts = []\n\n[multiplex]\nenabled = true\n").unwrap();
+    assert_eq!(cfg.multiplex.persist_for(&own), Some("1h"));
+    assert_eq!(cfg.multiplex.persist_for(&plain), Some("10m"));
+}
+
+#[test]
+fn test_load_error_location() {
+    let source = "[[hosts]]\nname = \"a\"\nuser = \"u\"\nhost = \"h\"\n\n[[hosts]]\nname = \"b\"\nuser = \"u\"\nhost = \"h\"\nport = 99999\n";
+    let err = Config::from_toml(source).unwrap_err();
+    assert_eq!(err, LoadError {
+        message: "invalid value: integer `99999`, expected u16".into(),
+        line: Some(10),
+        column: Some(8),
+        field: Some("hosts[1].port".into()),
+        source_line: Some("port = 99999".into()),
+    });
+    assert_eq!(err.to_string(), "line 10, column 8: invalid value: integer `99999`, expected u16 (in `hosts[1].port`)");
+
+    // Missing fields point at the table they're missing from.
+    let err = Config::from_toml("[[hosts]]\nname = \"a\"\nuser = \"u\"\nhost = \"h\"\n\n[[hosts]]\nname = \"b\"\nhost = \"h\"\n").unwrap_err();
+    assert_eq!(err.line, Some(6));
+    assert_eq!(err.field.as_deref(), Some("hosts[1].user"));
+
+    // Nested arrays count from their own parent.
+    let source = "[[hosts]]\nname = \"a\"\nuser = \"u\"\nhost = \"h\"\n[[hosts.forwards]]\nkind = \"local\"\nlisten = \"1\"\n\n[[hosts]]\nname = \"b\"\nuser = \"u\"\nhost = \"h\"\n[[hosts.forwards]]\nkind = \"sideways\"\nlisten = \"1\"\n";
+    assert_eq!(Config::from_toml(source).unwrap_err().field.as_deref(), Some("hosts[1].forwards[0].kind"));
+
+    // Syntax errors still get a position.
+    let err = Config::from_toml("[health_check]\ninterval = 5\ntimeout = \"3\n").unwrap_err();
+    assert_eq!(err.line, Some(3));
+    assert_eq!(err.field.as_deref(), Some("health_check.timeout"));
+}
+
+#[test]
+fn test_edits_keep_formatting() {
+    let source = "\
+version = 1
+
+# My hosts
+
+# production
+[[hosts]]
+name   = \"web\"   # the main one
+user = \"deploy\"
+host = \"192.0.2.10\"
+
+[[hosts.forwards]]
+kind = \"local\"
+listen = \"8080\"
+target = \"localhost:80\"
+
+# database
+[[hosts]]
+host = \"192.0.2.20\"
+name = \"db\"
+user = \"root\"
+
+[health_check]
+interval = 30 # seconds
+";
+    let mut config = Config::from_toml(source).unwrap();
+    assert_eq!(config.to_toml().unwrap(), source);
+
+    let mut db = config.hosts[1].clone();
+    db.name = "database".into();
+    db.port = Some(5432);
+    config.update_host(1, db);
+    config.add_host(Host { name: "new".into(), user: "u".into(), host: "h".into(), ..Default::default() });
+    let edited = config.to_toml().unwrap();
+    assert_eq!(edited, source
+        .replace("name = \"db\"", "name = \"database\"")
+        .replace("user = \"root\"\n", "user = \"root\"\nport = 5432\n\n[[hosts]]\nname = \"new\"\nuser = \"u\"\nhost = \"h\"\n"));
+
+    config.remove_host(0);
+    let removed = config.to_toml().unwrap();
+    assert!(removed.starts_with("version = 1\n\n# My hosts\n\n# database\n[[hosts]]\nhost = \"192.0.2.20\"\n"));
+    assert!(!removed.contains("web") && !removed.contains("forwards"));
+    assert!(removed.ends_with("[health_check]\ninterval = 30 # seconds\n"));
+    let reloaded = Config::from_toml(&removed).unwrap();
+    assert_eq!(reloaded.hosts.iter().map(|h| h.name.as_str()).collect::<Vec<_>>(), ["database", "new"]);
+}
+
+#[test]
+fn test_undoing_a_removal_keeps_comments_with_their_host() {
+    let source = "version = 1\n\n# web server\n[[hosts]]\nname = \"web\"\nuser = \"deploy\"\nhost = \"192.0.2.10\"\n\n# database\n[[hosts]]\nname = \"db\"\nuser = \"root\"\nhost = \"192.0.2.20\"\n";
+    let mut config = Config::from_toml(source).unwrap();
+
+    // What the TUI does when saving a delete fails.
+    let before = config.clone();
+    config.remove_host(0);
+    config = before;
+
+    let mut db = config.hosts[1].clone();
+    db.port = Some(5432);
+    config.update_host(1, db);
+    assert_eq!(config.to_toml().unwrap(), source.replace("host = \"192.0.2.20\"\n", "host = \"192.0.2.20\"\nport = 5432\n"));
+}
+
+#[test]
+fn test_save_reports_errors() {
+    let home = std::env::temp_dir().join(format!("lazyssh-save-test-{}", std::process::id()));
+    std::fs::create_dir_all(home.join(".lazyssh")).unwrap();
+    std::env::set_var("HOME", &home);
+
+    let mut config = Config::default();
+    config.add_host(Host { name: "web".into(), user: "root".into(), host: "10.0.0.1".into(), ..Default::default() });
+    config.save().unwrap();
+
+    // Edited by hand since it was loaded, and now broken.
+    let broken = "[[hosts]]\nname = \"web\"\nport = \"22\n";
+    std::fs::write(Config::path(), broken).unwrap();
+    let err = config.save().unwrap_err();
+    assert!(matches!(&err, SaveError::Invalid(e) if e.line == Some(3)));
+    assert!(err.to_string().contains("not saving over"));
+    assert_eq!(std::fs::read_to_string(Config::path()).unwrap(), broken);
+
+    std::fs::remove_dir_all(&home).ok();
+}
@@ -0,0 +1,102 @@
+use lazyssh::config::Host;
+use lazyssh::exec::{run, select_hosts, summary_table, ExecResult};
+use lazyssh::ssh::exec_args;
+use std::sync::Mutex;
+use std::time::Duration;
+
+fn host(name: &str, group: Option<&str>, tags: &[&str]) -> Host {
+    Host {
+        name: name.into(),
+        user: "deploy".into(),
+        host: format!("{}.example", name),
+        group: group.map(String::from),
+        tags: tags.iter().map(|t| t.to_string()).collect(),
+        ..Default::default()
+    }
+}
+
+fn names(hosts: Vec<&Host>) -> Vec<&str> {
+    hosts.into_iter().map(|h| h.name.as_str()).collect()
+}
+
+#[test]
+fn test_select_hosts() {
+    let hosts = vec![
+        host("web-1", Some("prod/eu"), &[]),
+        host("web-2", Some("prod/us"), &["legacy"]),
+        host("db-1", Some("prod"), &["db"]),
+        host("dev", None, &[]),
+    ];
+    assert_eq!(names(select_hosts(&hosts, "web-*")), vec!["web-1", "web-2"]);
+    assert_eq!(names(select_hosts(&hosts, "group:prod")), vec!["web-1", "web-2", "db-1"]);
+    assert_eq!(names(select_hosts(&hosts, "group:prod/eu")), vec!["web-1"]);
+    assert_eq!(names(select_hosts(&hosts, "tag:db, dev")), vec!["db-1", "dev"]);
+    assert!(select_hosts(&hosts, "group:pro").is_empty());
+    assert!(select_hosts(&hosts, "").is_empty());
+}
+
+#[test]
+fn test_exec_args() {
+    let mut h = host("web", None, &[]);
+    h.command = Some("cd /srv".into());
+    let args = exec_args(&h, "uptime");
+    assert_eq!(args[0], "-T");
+    assert!(args.windows(2).any(|w| w == ["-o", "BatchMode=yes"]));
+    assert_eq!(&args[args.len() - 2..], ["deploy@web.example", "uptime"]);
+
+    h.password = Some("pw".into());
+    assert!(!exec_args(&h, "uptime").contains(&"BatchMode=yes".to_string()));
+}
+
+#[test]
+fn test_summary_table() {
+    let result = |name: &str, code: Option<i32>, error: Option<&str>| ExecResult {
+        name: name.into(),
+        code,
+        error: error.map(String::from),
+        duration: Duration::from_millis(1500),
+        output: vec![],
+    };
+    let table = summary_table(&[
+        result("web-1", Some(0), None),
+        result("db", Some(255), None),
+        result("a", None, Some("jump host cycle: a → b → a")),
+    ]);
+    assert_eq!(table, "\
+HOST     EXIT      TIME
+web-1       0     1.50s
+db        255     1.50s
+a           -     1.50s  jump host cycle: a → b → a
+");
+}
+
+#[cfg(unix)]
+#[test]
+fn test_run_collects_output_and_exit_codes() {
+    use std::os::unix::fs::PermissionsExt;
+
+    // A stand-in ssh that echoes the command and fails for "bad.example".
+    let dir = std::env::temp_dir().join(format!("lazyssh-exec-test-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let fake = dir.join("ssh");
+    std::fs::write(&fake, "#!/bin/sh\nfor last; do :; done\necho \"ran $last\"\necho oops >&2\ncase \"$*\" in *bad.example*) exit 3;; esac\n").unwrap();
+    std::fs::set_permissions(&fake, std::fs::Permissions::from_mode(0o755)).unwrap();
+    let path = format!("{}:{}", dir.display(), std::env::var("PATH").unwrap_or_default());
+    std::env::set_var("PATH", path);
+
+    let targets = vec![host("good", None, &[]), host("bad", None, &[]), host("good2", None, &[])];
+    let seen = Mutex::new(Vec::new());
+    let results = run(&targets, &targets, "uptime", 2, |name, line| {
+        seen.lock().unwrap().push(format!("{}: {}", name, line));
+    });
+
+    assert_eq!(results.iter().map(|r| r.name.as_str()).collect::<Vec<_>>(), vec!["good", "bad", "good2"]);
+    assert_eq!(results.iter().map(|r| r.code).collect::<Vec<_>>(), vec![Some(0), Some(3), Some(0)]);
+    assert!(results[0].success() && !results[1].success());
+    let mut output = results[0].output.clone();
+    output.sort();
+    assert_eq!(output, vec!["oops", "ran uptime"]);
+    assert_eq!(seen.lock().unwrap().len(), 6);
+
+    std::fs::remove_dir_all(&dir).ok();
+}
@@ -0,0 +1,76 @@
+use lazyssh::config::Host;
+use lazyssh::filter::{filter_hosts, fuzzy_match};
+
+fn host(name: &str, user: &str, addr: &str, command: Option<&str>) -> Host {
+    Host {
+        name: name.into(),
+        user: user.into(),
+        host: addr.into(),
+        command: command.map(String::from),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_fuzzy_match_positions() {
+    assert_eq!(fuzzy_match("pw", "prod-web").unwrap().1, vec![0, 5]);
+    assert_eq!(fuzzy_match("WEB", "prod-web").unwrap().1, vec![5, 6, 7]);
+    assert_eq!(fuzzy_match("xyz", "prod-web"), None);
+    assert_eq!(fuzzy_match("", "anything"), Some((0, vec![])));
+}
+
+#[test]
+fn test_fuzzy_match_prefers_word_starts_and_runs() {
+    let (word_start, _) = fuzzy_match("pw", "prod-web").unwrap();
+    let (scattered, _) = fuzzy_match("pw", "groupware").unwrap();
+    assert!(word_start > scattered);
+
+    // The later, consecutive "db" beats the early scattered d…b.
+    assert_eq!(fuzzy_match("db", "dev-box-db").unwrap().1, vec![8, 9]);
+}
+
+#[test]
+fn test_filter_hosts_across_fields() {
+    let hosts = vec![
+        host("web1", "deploy", "10.0.0.1", None),
+        host("db1", "postgres", "10.0.0.2", None),
+        host("cache", "redis", "10.0.0.3", Some("redis-cli monitor")),
+    ];
+
+    let names = |query: &str| -> Vec<String> {
+        filter_hosts(&hosts, query).into_iter().map(|m| hosts[m.idx].name.clone()).collect()
+    };
+    assert_eq!(names("db"), vec!["db1"]);
+    assert_eq!(names("postgres"), vec!["db1"]);
+    assert_eq!(names("monitor"), vec!["cache"]);
+    assert_eq!(names("10.0.0.3"), vec!["cache"]);
+    assert_eq!(names("w 1"), vec!["web1"]);
+    assert!(names("nope").is_empty());
+
+    let m = &filter_hosts(&hosts, "web")[0];
+    assert_eq!(m.name_positions, vec![0, 1, 2]);
+    assert!(m.host_positions.is_empty());
+}
+
+#[test]
+fn test_tag_expressions() {
+    let tagged = |name: &str, tags: &[&str]| Host {
+        name: name.into(),
+        tags: tags.iter().map(|t| t.to_string()).collect(),
+        ..Default::default()
+    };
+    let hosts = vec![
+        tagged("pg-main", &["db"]),
+        tagged("pg-old", &["db", "legacy"]),
+        tagged("web", &["k8s"]),
+    ];
+    let names = |query: &str| -> Vec<String> {
+        filter_hosts(&hosts, query).into_iter().map(|m| hosts[m.idx].name.clone()).collect()
+    };
+    assert_eq!(names("tag:db"), vec!["pg-main", "pg-old"]);
+    assert_eq!(names("tag:DB !tag:legacy"), vec!["pg-main"]);
+    assert_eq!(names("!tag:db"), vec!["web"]);
+    assert_eq!(names("tag:db old"), vec!["pg-old"]);
+    // Incomplete expressions don't filter anything out.
+    assert_eq!(names("tag:").len(), 3);
+}
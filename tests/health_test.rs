@@ -0,0 +1,76 @@
+use lazyssh::config::{HealthCheck, Host};
+use lazyssh::health::{format_ago, format_latency, is_checkable, probe, HealthChecker};
+use std::io::Write;
+use std::net::TcpListener;
+use std::time::{Duration, Instant};
+
+/// A listener that sends `banner` to the first client.
+fn banner_server(banner: &'static str) -> u16 {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = listener.local_addr().unwrap().port();
+    std::thread::spawn(move || {
+        for mut stream in listener.incoming().flatten() {
+            let _ = stream.write_all(banner.as_bytes());
+        }
+    });
+    port
+}
+
+fn closed_port() -> u16 {
+    TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap().port()
+}
+
+#[test]
+fn test_probe_reads_banner() {
+    let port = banner_server("Welcome\r\nSSH-2.0-OpenSSH_9.6\r\n");
+    let result = probe("127.0.0.1", port, Duration::from_secs(2), true).unwrap();
+    assert_eq!(result.banner.as_deref(), Some("SSH-2.0-OpenSSH_9.6"));
+
+    let result = probe("127.0.0.1", port, Duration::from_secs(2), false).unwrap();
+    assert_eq!(result.banner, None);
+}
+
+#[test]
+fn test_probe_refused() {
+    assert!(probe("127.0.0.1", closed_port(), Duration::from_secs(2), true).is_err());
+}
+
+#[test]
+fn test_formatting() {
+    assert_eq!(format_latency(Duration::from_millis(12)), "12ms");
+    assert_eq!(format_latency(Duration::from_millis(1500)), "1.5s");
+    assert_eq!(format_ago(Duration::from_secs(42)), "42s ago");
+    assert_eq!(format_ago(Duration::from_secs(300)), "5m ago");
+    assert_eq!(format_ago(Duration::from_secs(7200)), "2h ago");
+}
+
+#[test]
+fn test_checker_caches_results() {
+    let host = |name: &str, port: u16| Host { name: name.into(), user: "u".into(), host: "127.0.0.1".into(), port: Some(port), ..Default::default() };
+    let up = host("up", banner_server("SSH-2.0-test\n"));
+    let down = host("down", closed_port());
+    let mut behind_jump = host("inner", 22);
+    behind_jump.jump = Some("up".into());
+    assert!(!is_checkable(&behind_jump));
+
+    let hosts = vec![up.clone(), down.clone(), behind_jump.clone()];
+    let checker = HealthChecker::start(&hosts, &HealthCheck { interval: 60, read_banner: true, ..Default::default() });
+    assert!(checker.is_enabled());
+    let started = Instant::now();
+    while (checker.status(&up).is_none() || checker.status(&down).is_none()) && started.elapsed() < Duration::from_secs(5) {
+        std::thread::sleep(Duration::from_millis(20));
+    }
+
+    let up_status = checker.status(&up).unwrap();
+    assert!(up_status.is_up() && up_status.last_seen.is_some());
+    assert!(!checker.status(&down).unwrap().is_up());
+    assert!(checker.status(&behind_jump).is_none());
+
+    // A result isn't reused once the address changes.
+    let mut moved = up.clone();
+    moved.port = Some(1);
+    assert!(checker.status(&moved).is_none());
+
+    let disabled = HealthChecker::start(&hosts, &HealthCheck::default());
+    assert!(!disabled.is_enabled());
+}
@@ -0,0 +1,45 @@
+use lazyssh::history::{History, HostStats, Record};
+
+fn record(host: &str, timestamp: u64, exit_code: Option<i32>) -> Record {
+    Record { host: host.into(), timestamp, duration: 60, exit_code }
+}
+
+#[test]
+fn test_append_and_reload() {
+    let path = std::env::temp_dir().join(format!("lazyssh-history-{}.jsonl", std::process::id()));
+    std::fs::remove_file(&path).ok();
+
+    let mut history = History::load(&path);
+    assert!(history.stats().is_empty());
+    history.append(&path, record("web", 100, Some(0))).unwrap();
+    history.append(&path, record("web", 200, Some(255))).unwrap();
+    history.append(&path, record("db", 150, None)).unwrap();
+
+    // A truncated last line is skipped rather than losing the whole file.
+    let mut text = std::fs::read_to_string(&path).unwrap();
+    text.push_str("{\"host\":\"we");
+    std::fs::write(&path, text).unwrap();
+
+    let stats = History::load(&path).stats();
+    assert_eq!(stats.len(), 2);
+    assert_eq!(stats["web"], HostStats { count: 2, last: 200, last_exit: Some(255) });
+    assert_eq!(stats["db"], HostStats { count: 1, last: 150, last_exit: None });
+
+    std::fs::remove_file(&path).ok();
+}
+
+#[test]
+fn test_stats_use_latest_record() {
+    let path = std::env::temp_dir().join(format!("lazyssh-history-order-{}.jsonl", std::process::id()));
+    let lines = [record("web", 300, Some(0)), record("web", 100, Some(1))]
+        .iter()
+        .map(|r| serde_json::to_string(r).unwrap())
+        .collect::<Vec<_>>()
+        .join("\n");
+    std::fs::write(&path, lines).unwrap();
+
+    let stats = History::load(&path).stats();
+    assert_eq!(stats["web"], HostStats { count: 2, last: 300, last_exit: Some(0) });
+
+    std::fs::remove_file(&path).ok();
+}
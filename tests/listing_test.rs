@@ -0,0 +1,83 @@
+use lazyssh::config::{Forward, Host};
+use lazyssh::listing::{render, ListFormat, JSON_FORMAT_VERSION, REDACTED};
+
+fn hosts() -> Vec<Host> {
+    vec![
+        Host {
+            name: "web".into(),
+            user: "deploy".into(),
+            host: "10.0.0.1".into(),
+            port: Some(2222),
+            password: Some("hunter2".into()),
+            group: Some("prod/eu".into()),
+            tags: vec!["db".into(), "k8s".into()],
+            forwards: vec![Forward::parse("D 1080").unwrap()],
+            command: Some("cd /srv\ttail -f log".into()),
+            control_persist: Some("5m".into()),
+            ..Default::default()
+        },
+        Host {
+            name: "db".into(),
+            user: "pg".into(),
+            host: "10.0.0.2".into(),
+            ..Default::default()
+        },
+    ]
+}
+
+#[test]
+fn test_list_format_parse() {
+    assert_eq!(ListFormat::parse("json"), Some(ListFormat::Json));
+    assert_eq!(ListFormat::parse("tsv"), Some(ListFormat::Tsv));
+    assert_eq!(ListFormat::parse("table"), Some(ListFormat::Table));
+    assert_eq!(ListFormat::parse("yaml"), None);
+}
+
+#[test]
+fn test_json_is_versioned_and_redacted() {
+    let out = render(&hosts(), ListFormat::Json, false);
+    assert!(!out.contains("hunter2"));
+
+    let value: serde_json::Value = serde_json::from_str(&out).unwrap();
+    assert_eq!(value["version"], JSON_FORMAT_VERSION);
+    let web = &value["hosts"][0];
+    assert_eq!(web["password"], REDACTED);
+    assert_eq!(web["port"], 2222);
+    assert_eq!(web["group"], "prod/eu");
+    assert_eq!(web["tags"], serde_json::json!(["db", "k8s"]));
+    assert_eq!(web["forwards"][0], serde_json::json!({"kind": "dynamic", "listen": "1080", "target": null}));
+    assert_eq!(web["control_persist"], "5m");
+
+    let db = &value["hosts"][1];
+    assert_eq!(db["port"], 22);
+    assert_eq!(db["password"], serde_json::Value::Null);
+    assert_eq!(db["command"], serde_json::Value::Null);
+    assert_eq!(db["control_persist"], serde_json::Value::Null);
+}
+
+#[test]
+fn test_show_secrets() {
+    let out = render(&hosts(), ListFormat::Json, true);
+    let value: serde_json::Value = serde_json::from_str(&out).unwrap();
+    assert_eq!(value["hosts"][0]["password"], "hunter2");
+    assert!(render(&hosts(), ListFormat::Table, true).contains("hunter2"));
+    assert!(!render(&hosts(), ListFormat::Tsv, false).contains("hunter2"));
+}
+
+#[test]
+fn test_tsv_rows() {
+    let out = render(&hosts(), ListFormat::Tsv, false);
+    let lines: Vec<&str> = out.lines().collect();
+    assert_eq!(lines.len(), 2);
+    assert_eq!(lines[0].split('\t').collect::<Vec<_>>(), vec!["web", "deploy", "10.0.0.1", "2222", "prod/eu", "db,k8s", REDACTED]);
+    assert_eq!(lines[1], "db\tpg\t10.0.0.2\t22\t\t\t");
+}
+
+#[test]
+fn test_table_alignment() {
+    let out = render(&hosts(), ListFormat::Table, false);
+    let lines: Vec<&str> = out.lines().collect();
+    assert!(lines[0].starts_with("NAME  USER    HOST      PORT"));
+    assert!(lines[1].starts_with("web   deploy  10.0.0.1  2222"));
+    assert_eq!(lines[2].trim_end(), "db    pg      10.0.0.2  22");
+}
@@ -0,0 +1,82 @@
+use lazyssh::config::{Config, ForwardKind};
+use lazyssh::migrate::{upgrade, CURRENT};
+use std::fs;
+use std::path::Path;
+
+fn fixture(name: &str) -> String {
+    fs::read_to_string(Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/config").join(name)).unwrap()
+}
+
+/// Upgrades a fixture, checking that it keeps its comments and that the
+/// result is current and stable.
+fn upgraded(name: &str) -> Config {
+    let source = fixture(name);
+    let upgraded = upgrade(&source).unwrap();
+    assert!(upgraded.starts_with(&format!("version = {}\n\n", CURRENT)));
+    for comment in source.lines().filter(|l| l.starts_with('#')) {
+        assert!(upgraded.contains(comment), "{}: lost {:?}", name, comment);
+    }
+    assert_eq!(upgrade(&upgraded), None);
+    let config = Config::from_toml(&upgraded).unwrap();
+    assert_eq!(config.version, CURRENT);
+    config
+}
+
+#[test]
+fn test_upgrade_0_3_3() {
+    let config = upgraded("v0-0.3.3.toml");
+    assert_eq!(config.hosts.len(), 2);
+    assert_eq!(config.hosts[0].password.as_deref(), Some("your_password_here"));
+    assert_eq!(config.hosts[1].command.as_deref(), Some("cd /var/log\ntail -f application.log\n"));
+    assert!(config.vault.is_none());
+}
+
+#[test]
+fn test_upgrade_vault() {
+    let mut config = upgraded("v0-vault.toml");
+    config.unlock("fixture").unwrap();
+    let db = &config.hosts[1];
+    assert_eq!(db.password.as_deref(), Some("hunter2"));
+    assert!(db.identities_only);
+    assert_eq!(config.resolve_jump(db).unwrap().jump.as_deref(), Some("ops@203.0.113.1"));
+}
+
+#[test]
+fn test_upgrade_unversioned() {
+    let config = upgraded("v0-unversioned.toml");
+    let api = &config.hosts[0];
+    assert_eq!(api.group_path(), ["prod", "eu"]);
+    assert!(api.has_tag("k8s"));
+    assert_eq!(api.forwards.len(), 2);
+    assert_eq!(api.forwards[1].kind, ForwardKind::Dynamic);
+    assert_eq!(config.multiplex.persist_for(api), Some("5m"));
+    assert_eq!((config.health_check.interval, config.backups.keep), (60, 3));
+}
+
+#[test]
+fn test_current_and_newer_versions() {
+    assert_eq!(upgrade(&fixture("v1.toml")), None);
+    assert_eq!(Config::from_toml(&fixture("v1.toml")).unwrap().hosts[0].name, "web");
+
+    let err = Config::from_toml("hosts = []\n  version = 99\n").unwrap_err();
+    assert_eq!((err.line, err.column, err.field.as_deref()), (Some(2), Some(3), Some("version")));
+    assert_eq!(upgrade("version = 99\nhosts = []\n"), None);
+}
+
+#[test]
+fn test_load_upgrades_after_backup() {
+    let home = std::env::temp_dir().join(format!("lazyssh-migrate-test-{}", std::process::id()));
+    fs::create_dir_all(home.join(".lazyssh")).unwrap();
+    std::env::set_var("HOME", &home);
+    let source = fixture("v0-0.3.3.toml");
+    fs::write(Config::path(), &source).unwrap();
+
+    let config = Config::load().unwrap();
+    assert_eq!(config.version, CURRENT);
+    assert_eq!(fs::read_to_string(Config::path()).unwrap(), upgrade(&source).unwrap());
+    let backups = lazyssh::backup::list(&lazyssh::backup::backups_dir()).unwrap();
+    assert_eq!(backups.len(), 1);
+    assert_eq!(fs::read_to_string(&backups[0].path).unwrap(), source);
+
+    fs::remove_dir_all(&home).ok();
+}
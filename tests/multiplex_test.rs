@@ -0,0 +1,35 @@
+use lazyssh::multiplex::{is_live, socket_name};
+
+#[test]
+fn test_socket_name() {
+    assert_eq!(socket_name("web"), socket_name("web"));
+    assert_ne!(socket_name("web"), socket_name("Web"));
+    assert!(socket_name("web").starts_with("web-"));
+
+    // Unsafe characters are replaced, and the hash tells apart names
+    // that only differ after the shortened prefix.
+    let long = socket_name("prod/eu west/very-long-host-name-1");
+    assert!(long.starts_with("prod_eu_west_ver-"));
+    assert_ne!(long, socket_name("prod/eu west/very-long-host-name-2"));
+    assert!(long.len() <= 25);
+}
+
+#[cfg(unix)]
+#[test]
+fn test_is_live() {
+    use std::os::unix::net::UnixListener;
+
+    let socket = std::env::temp_dir().join(format!("lazyssh-mux-test-{}.sock", std::process::id()));
+    std::fs::remove_file(&socket).ok();
+    assert!(!is_live(&socket));
+
+    let listener = UnixListener::bind(&socket).unwrap();
+    assert!(is_live(&socket));
+
+    // A socket file whose master has gone away isn't live.
+    drop(listener);
+    assert!(socket.exists());
+    assert!(!is_live(&socket));
+
+    std::fs::remove_file(&socket).ok();
+}
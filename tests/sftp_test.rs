@@ -0,0 +1,110 @@
+use lazyssh::sftp::{human_size, list_local, master_pid, parse_listing, quote, remote_join, remote_parent};
+
+#[test]
+fn test_parse_listing() {
+    let output = "\
+drwxr-xr-x    5 deploy   deploy       4096 Jan 10 12:00 .
+drwxr-xr-x    3 root     root         4096 Jan  1  2024 ..
+-rw-r--r--    1 deploy   deploy        220 Jan  1  2024 .profile
+-rw-r--r--    1 deploy   deploy      10240 Mar  3 09:15 release notes.txt
+lrwxrwxrwx    1 deploy   deploy         12 Mar  3 09:15 current
+drwxr-xr-x    2 deploy   deploy       4096 Feb  2 08:00 Logs
+not a listing line";
+    let entries = parse_listing(output);
+    let names: Vec<&str> = entries.iter().map(|e| e.name.as_str()).collect();
+    assert_eq!(names, vec!["Logs", ".profile", "current", "release notes.txt"]);
+    assert!(entries[0].is_dir);
+    assert!(entries[2].is_link && !entries[2].is_dir);
+    assert_eq!(entries[3].size, 10240);
+}
+
+#[test]
+fn test_remote_paths() {
+    assert_eq!(remote_join("/home/deploy", "logs"), "/home/deploy/logs");
+    assert_eq!(remote_join("/", "etc"), "/etc");
+    assert_eq!(remote_parent("/home/deploy"), "/home");
+    assert_eq!(remote_parent("/home/"), "/");
+    assert_eq!(remote_parent("/"), "/");
+    assert_eq!(quote(r#"a "b" \c"#), r#""a \"b\" \\c""#);
+}
+
+#[test]
+fn test_human_size() {
+    assert_eq!(human_size(512), "512B");
+    assert_eq!(human_size(1536), "1.5K");
+    assert_eq!(human_size(5 * 1024 * 1024), "5.0M");
+}
+
+#[test]
+fn test_list_local() {
+    let dir = std::env::temp_dir().join(format!("lazyssh-list-local-{}", std::process::id()));
+    std::fs::create_dir_all(dir.join("sub")).unwrap();
+    std::fs::write(dir.join("b.txt"), "hello").unwrap();
+    std::fs::write(dir.join("A.txt"), "").unwrap();
+
+    let entries = list_local(&dir).unwrap();
+    let names: Vec<&str> = entries.iter().map(|e| e.name.as_str()).collect();
+    assert_eq!(names, vec!["sub", "A.txt", "b.txt"]);
+    assert!(entries[0].is_dir);
+    assert_eq!(entries[2].size, 5);
+
+    std::fs::remove_dir_all(&dir).ok();
+}
+
+#[cfg(unix)]
+#[test]
+fn test_session_over_fake_master() {
+    use lazyssh::config::Host;
+    use lazyssh::sftp::Session;
+    use std::os::unix::fs::PermissionsExt;
+
+    // A stand-in ssh that "authenticates" by creating the control socket,
+    // and an sftp that logs its batch and answers a few commands.
+    let dir = std::env::temp_dir().join(format!("lazyssh-sftp-test-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let log = dir.join("log");
+    let ssh = "#!/bin/sh\n[ \"$1\" = -V ] && exit 0\nfor arg; do case \"$arg\" in ControlPath=*) sock=\"${arg#ControlPath=}\";; esac; done\nif [ \"$1\" = -O ]; then echo \"Master running (pid=$(cat \"$sock.pid\"))\" >&2; exit 0; fi\necho $$ > \"$sock.pid\"\ntouch \"$sock\"\nexec sleep 30\n";
+    let sftp = format!("#!/bin/sh\nwhile read -r line; do\necho \"sftp> $line\"\necho \"$line\" >> '{}'\ncase \"$line\" in\npwd) echo 'Remote working directory: /home/deploy';;\n'ls -la \"/home/deploy/old\"') echo '-rw-r--r--    1 deploy deploy 3 Jan  1 12:00 a.txt'; echo 'drwxr-xr-x    2 deploy deploy 4096 Jan  1 12:00 empty';;\nrename*) echo 'Couldn'\"'\"'t rename file: Permission denied' >&2; exit 1;;\nesac\ndone\n", log.display());
+    for (name, script) in [("ssh", ssh.to_string()), ("sftp", sftp)] {
+        std::fs::write(dir.join(name), script).unwrap();
+        std::fs::set_permissions(dir.join(name), std::fs::Permissions::from_mode(0o755)).unwrap();
+    }
+    let path = format!("{}:{}", dir.display(), std::env::var("PATH").unwrap_or_default());
+    std::env::set_var("PATH", path);
+    std::env::set_var("HOME", &dir);
+
+    let h = Host { name: "web".into(), user: "deploy".into(), host: "192.0.2.10".into(), ..Default::default() };
+    // The socket goes in the private sockets directory.
+    let sockets = dir.join(".lazyssh/sockets");
+    std::fs::create_dir_all(&sockets).unwrap();
+    std::fs::set_permissions(&sockets, std::fs::Permissions::from_mode(0o755)).unwrap();
+    assert!(Session::open(&h).err().unwrap().contains("open to other users"));
+    std::fs::set_permissions(&sockets, std::fs::Permissions::from_mode(0o700)).unwrap();
+
+    let session = Session::open(&h).unwrap();
+    assert_eq!(session.home, "/home/deploy");
+
+    session.remove("/home/deploy/old", true).unwrap();
+    let err = session.rename("/home/deploy/a", "/home/deploy/b").unwrap_err();
+    assert_eq!(err, "Couldn't rename file: Permission denied");
+    drop(session);
+
+    let logged = std::fs::read_to_string(&log).unwrap();
+    assert_eq!(logged.lines().collect::<Vec<_>>(), vec![
+        "pwd",
+        "ls -la \"/home/deploy/old\"",
+        "ls -la \"/home/deploy/old/empty\"",
+        "rmdir \"/home/deploy/old/empty\"",
+        "rm \"/home/deploy/old/a.txt\"",
+        "rmdir \"/home/deploy/old\"",
+        "rename \"/home/deploy/a\" \"/home/deploy/b\"",
+    ]);
+
+    std::fs::remove_dir_all(&dir).ok();
+}
+
+#[test]
+fn test_master_pid() {
+    assert_eq!(master_pid("Master running (pid=4242)"), Some(4242));
+    assert_eq!(master_pid("Control socket connect(/tmp/x): No such file or directory"), None);
+}
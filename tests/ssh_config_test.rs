@@ -0,0 +1,190 @@
+use lazyssh::ssh_config::{parse, wildcard_match};
+use std::fs;
+use std::path::Path;
+
+#[test]
+fn test_parse_basic_hosts() {
+    let content = r#"
+# production
+Host web-server web2
+    HostName 192.168.1.10
+    User deploy
+    Port 2222
+    IdentityFile ~/.ssh/id_web
+    IdentitiesOnly yes
+
+Host bastion
+    HostName=bastion.example.com
+    User admin
+
+Host db
+    HostName 10.0.0.5
+    ProxyJump bastion
+    LocalForward 5432 localhost:5432
+    DynamicForward 1080
+"#;
+    let hosts = parse(content, Path::new("/nonexistent"));
+    assert_eq!(hosts.len(), 4);
+
+    assert_eq!(hosts[0].name, "web-server");
+    assert_eq!(hosts[0].host, "192.168.1.10");
+    assert_eq!(hosts[0].user, "deploy");
+    assert_eq!(hosts[0].port, Some(2222));
+    assert_eq!(hosts[0].identity_file.as_deref(), Some("~/.ssh/id_web"));
+    assert!(hosts[0].identities_only);
+    assert_eq!(hosts[1].name, "web2");
+    assert_eq!(hosts[1].host, "192.168.1.10");
+
+    assert_eq!(hosts[2].host, "bastion.example.com");
+    assert_eq!(hosts[2].user, "admin");
+    assert_eq!(hosts[2].port, None);
+    assert!(!hosts[2].identities_only);
+
+    assert_eq!(hosts[3].jump.as_deref(), Some("bastion"));
+    let forwards: Vec<String> = hosts[3].forwards.iter().map(|f| f.to_string()).collect();
+    assert_eq!(forwards, vec!["L 5432:localhost:5432", "D 1080"]);
+}
+
+#[test]
+fn test_parse_wildcard_defaults_first_value_wins() {
+    let content = r#"
+Host app
+    User alice
+
+Host *.internal app
+    Port 2200
+
+Host *
+    User nobody
+    Port 22
+"#;
+    let hosts = parse(content, Path::new("/nonexistent"));
+    assert_eq!(hosts.len(), 1);
+    assert_eq!(hosts[0].name, "app");
+    assert_eq!(hosts[0].host, "app");
+    assert_eq!(hosts[0].user, "alice");
+    assert_eq!(hosts[0].port, Some(2200));
+}
+
+#[test]
+fn test_parse_skips_match_and_negated_patterns() {
+    let content = r#"
+Host * !legacy
+    User modern
+
+Match host legacy
+    User ignored
+
+Host legacy
+    HostName %h.example.com
+    ProxyJump none
+"#;
+    let hosts = parse(content, Path::new("/nonexistent"));
+    assert_eq!(hosts.len(), 1);
+    assert_eq!(hosts[0].name, "legacy");
+    assert_eq!(hosts[0].host, "legacy.example.com");
+    assert_ne!(hosts[0].user, "modern");
+    assert_ne!(hosts[0].user, "ignored");
+    assert_eq!(hosts[0].jump, None);
+}
+
+#[test]
+fn test_parse_include_glob() {
+    let dir = std::env::temp_dir().join(format!("lazyssh-include-{}", std::process::id()));
+    fs::create_dir_all(dir.join("conf.d")).unwrap();
+    fs::write(dir.join("conf.d/10-a.conf"), "Host alpha\n  User a\n").unwrap();
+    fs::write(dir.join("conf.d/20-b.conf"), "Host beta\n  User b\n").unwrap();
+    fs::write(dir.join("conf.d/ignored.txt"), "Host gamma\n").unwrap();
+
+    let hosts = parse("Include conf.d/*.conf\n\nHost delta\n  User d\n", &dir);
+    let names: Vec<&str> = hosts.iter().map(|h| h.name.as_str()).collect();
+    assert_eq!(names, vec!["alpha", "beta", "delta"]);
+    assert_eq!(hosts[1].user, "b");
+
+    fs::remove_dir_all(&dir).ok();
+}
+
+#[test]
+fn test_wildcard_match() {
+    assert!(wildcard_match("*", "anything"));
+    assert!(wildcard_match("web-*", "web-01"));
+    assert!(wildcard_match("db?", "db1"));
+    assert!(wildcard_match("*.example.com", "a.b.example.com"));
+    assert!(!wildcard_match("db?", "db12"));
+    assert!(!wildcard_match("web-*", "api-01"));
+}
+
+#[test]
+fn test_render_round_trip() {
+    use lazyssh::config::{Config, Forward, Host};
+
+    let config = Config {
+        hosts: vec![
+            Host {
+                name: "web server".into(),
+                user: "deploy".into(),
+                host: "192.0.2.10".into(),
+                port: Some(2222),
+                identity_file: Some("~/.ssh/id_ed25519".into()),
+                ..Default::default()
+            },
+            Host {
+                name: "db".into(),
+                user: "root".into(),
+                host: "10.0.0.5".into(),
+                jump: Some("web server, bastion".into()),
+                control_persist: Some("5m".into()),
+                forwards: vec![Forward::parse("R 8080:localhost:80").unwrap()],
+                ..Default::default()
+            },
+        ],
+        ..Default::default()
+    };
+    let rendered = config.to_ssh_config();
+    assert!(rendered.starts_with('#'));
+    assert!(rendered.contains("Host web-server\n    HostName 192.0.2.10\n    User deploy\n    Port 2222\n"));
+    assert!(rendered.contains("    ProxyJump web-server,bastion\n"));
+    assert!(rendered.contains("    RemoteForward 8080 localhost:80\n"));
+    let socket = lazyssh::multiplex::socket_path(&config.hosts[1]);
+    assert!(rendered.contains(&format!("    ControlMaster auto\n    ControlPath {}\n    ControlPersist 5m\n", socket.display())));
+    assert_eq!(rendered.matches("ControlMaster").count(), 1);
+
+    let parsed = parse(&rendered, Path::new("/nonexistent"));
+    assert_eq!(parsed.len(), 2);
+    assert_eq!(parsed[0].name, "web-server");
+    assert_eq!(parsed[0].port, Some(2222));
+    assert_eq!(parsed[0].identity_file.as_deref(), Some("~/.ssh/id_ed25519"));
+    assert_eq!(parsed[1].user, "root");
+    assert_eq!(parsed[1].jump.as_deref(), Some("web-server,bastion"));
+    assert_eq!(parsed[1].forwards, config.hosts[1].forwards);
+    assert_eq!(parsed[1].control_persist.as_deref(), Some("5m"));
+    assert_eq!(parsed[0].control_persist, None);
+}
+
+#[test]
+fn test_render_escapes_patterns_and_quotes_values() {
+    use lazyssh::config::{Config, Host};
+
+    let config = Config {
+        hosts: vec![
+            Host { name: "web*".into(), user: "deploy".into(), host: "fe80::1%eth0".into(), ..Default::default() },
+            Host { name: "!db? primary".into(), user: "root".into(), host: "db host".into(), jump: Some("web*".into()), ..Default::default() },
+            Host { name: "a,b".into(), user: "ops".into(), host: "10.0.0.7".into(), ..Default::default() },
+        ],
+        ..Default::default()
+    };
+    let rendered = config.to_ssh_config();
+    assert!(rendered.contains("Host web_\n    HostName fe80::1%%eth0\n"));
+    assert!(rendered.contains("Host _db_-primary\n    HostName \"db host\"\n"));
+    assert!(rendered.contains("    ProxyJump web_\n"));
+    assert!(rendered.contains("Host a_b\n"));
+
+    let parsed = parse(&rendered, Path::new("/nonexistent"));
+    let names: Vec<_> = parsed.iter().map(|h| h.name.as_str()).collect();
+    assert_eq!(names, ["web_", "_db_-primary", "a_b"]);
+    assert_eq!(parsed[0].host, "fe80::1%eth0");
+    assert_eq!(parsed[1].host, "db host");
+    assert_eq!(parsed[1].user, "root");
+    assert_eq!(parsed[1].jump.as_deref(), Some("web_"));
+    assert_eq!(parsed[2].user, "ops");
+}
@@ -0,0 +1,230 @@
+use lazyssh::config::{Forward, Host};
+use lazyssh::ssh::{askpass_answer, command, command_with, connect_args, control_args, master_args, multiplex_args, parse_openssh_version, prompt_identity, resolve_prompt_target, scp_args, sftp_args, tunnel_args, PasswordMethod, Transfer, TransferDirection};
+use std::ffi::OsStr;
+use std::path::PathBuf;
+
+fn host() -> Host {
+    Host {
+        name: "web".into(),
+        user: "deploy".into(),
+        host: "192.0.2.10".into(),
+        port: Some(2222),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_connect_args() {
+    let mut h = host();
+    let args = connect_args(&h);
+    assert_eq!(args.first().map(String::as_str), Some("-t"));
+    assert!(args.windows(2).any(|w| w == ["-p", "2222"]));
+    assert_eq!(args.last().map(String::as_str), Some("deploy@192.0.2.10"));
+
+    h.identity_file = Some("~/.ssh/id_web".into());
+    h.identities_only = true;
+    let args = connect_args(&h);
+    assert!(args.windows(2).any(|w| w == ["-i", "~/.ssh/id_web"]));
+    assert!(args.windows(2).any(|w| w == ["-o", "IdentitiesOnly=yes"]));
+
+    h.jump = Some("admin@10.0.0.1:2222".into());
+    let args = connect_args(&h);
+    assert!(args.windows(2).any(|w| w == ["-J", "admin@10.0.0.1:2222"]));
+
+    h.command = Some("cd /var/www\n\nls -la\n".into());
+    let args = connect_args(&h);
+    assert_eq!(args[args.len() - 2], "deploy@192.0.2.10");
+    assert_eq!(args[args.len() - 1], "cd /var/www; ls -la; exec $SHELL -l");
+}
+
+#[test]
+fn test_command_without_password_runs_ssh_directly() {
+    let h = host();
+    let cmd = command("ssh", &h, &connect_args(&h));
+    assert_eq!(cmd.get_program(), "ssh");
+    assert!(cmd.get_envs().all(|(k, _)| k != "SSHPASS"));
+}
+
+#[test]
+fn test_undecrypted_password_is_never_sent() {
+    let mut h = host();
+    h.password = Some("enc:v1:AAAA".into());
+    let cmd = command_with("ssh", &h, &connect_args(&h), Some(&PasswordMethod::Sshpass));
+    assert_eq!(cmd.get_program(), "ssh");
+    assert!(cmd.get_envs().all(|(k, _)| k != "SSHPASS"));
+}
+
+#[test]
+fn test_sshpass_keeps_password_off_the_command_line() {
+    let mut h = host();
+    h.password = Some("hunter2".into());
+    let cmd = command_with("ssh", &h, &connect_args(&h), Some(&PasswordMethod::Sshpass));
+
+    assert_eq!(cmd.get_program(), "sshpass");
+    let args: Vec<&OsStr> = cmd.get_args().collect();
+    assert_eq!(&args[..4], &[OsStr::new("-e"), OsStr::new("-P"), OsStr::new("deploy@192.0.2.10"), OsStr::new("ssh")]);
+    assert!(args.iter().all(|a| !a.to_string_lossy().contains("hunter2")));
+
+    let env: Vec<_> = cmd.get_envs().collect();
+    assert!(env.contains(&(OsStr::new("SSHPASS"), Some(OsStr::new("hunter2")))));
+}
+
+#[test]
+fn test_askpass_replaces_sshpass() {
+    let mut h = host();
+    h.password = Some("hunter2".into());
+    let exe = PathBuf::from("/usr/local/bin/lazyssh");
+    let cmd = command_with("ssh", &h, &connect_args(&h), Some(&PasswordMethod::Askpass(exe)));
+
+    assert_eq!(cmd.get_program(), "ssh");
+    let args: Vec<&OsStr> = cmd.get_args().collect();
+    assert!(args.iter().all(|a| !a.to_string_lossy().contains("hunter2")));
+
+    let env: Vec<_> = cmd.get_envs().collect();
+    assert!(env.contains(&(OsStr::new("SSH_ASKPASS"), Some(OsStr::new("/usr/local/bin/lazyssh")))));
+    assert!(env.contains(&(OsStr::new("SSH_ASKPASS_REQUIRE"), Some(OsStr::new("force")))));
+    assert!(env.contains(&(OsStr::new("LAZYSSH_ASKPASS_PASSWORD"), Some(OsStr::new("hunter2")))));
+    assert!(env.contains(&(OsStr::new("LAZYSSH_ASKPASS_TARGET"), Some(OsStr::new("deploy@192.0.2.10")))));
+}
+
+#[test]
+fn test_askpass_answer() {
+    let target = "deploy@192.0.2.10";
+    assert_eq!(askpass_answer("deploy@192.0.2.10's password: ", target, None, Some("pw")).as_deref(), Some("pw"));
+    assert_eq!(askpass_answer("(deploy@192.0.2.10) Password: ", target, None, Some("pw")).as_deref(), Some("pw"));
+    assert_eq!(askpass_answer("admin@bastion.example.com's password: ", target, None, Some("pw")), None);
+    assert_eq!(askpass_answer("xdeploy@192.0.2.10's password: ", target, None, Some("pw")), None);
+    assert_eq!(askpass_answer("Enter passphrase for key '/home/u/.ssh/id_ed25519': ", target, None, Some("pw")), None);
+    assert_eq!(askpass_answer("Are you sure you want to continue connecting (yes/no/[fingerprint])? ", target, None, Some("pw")), None);
+    assert_eq!(askpass_answer("Verification code: ", target, None, Some("pw")), None);
+    assert_eq!(askpass_answer("Password: ", target, None, Some("pw")), None);
+    assert_eq!(askpass_answer("deploy@192.0.2.10's password: ", target, None, None), None);
+}
+
+#[test]
+fn test_askpass_answer_ignores_host_case() {
+    assert_eq!(askpass_answer("deploy@web.example.com's password: ", "deploy@Web.Example.com", None, Some("pw")).as_deref(), Some("pw"));
+    assert_eq!(askpass_answer("(deploy@WEB) Password: ", "deploy@web", None, Some("pw")).as_deref(), Some("pw"));
+}
+
+#[test]
+fn test_askpass_answer_key_passphrase() {
+    let identity = Some("/home/u/.ssh/deploy_key");
+    let target = "deploy@192.0.2.10";
+    assert_eq!(askpass_answer("Enter passphrase for key '/home/u/.ssh/deploy_key': ", target, identity, Some("pw")).as_deref(), Some("pw"));
+    assert_eq!(askpass_answer("Enter passphrase for key '/home/u/.ssh/id_ed25519': ", target, identity, Some("pw")), None);
+    assert_eq!(askpass_answer("Enter passphrase for key '/home/u/.ssh/deploy_key.old': ", target, identity, Some("pw")), None);
+}
+
+#[test]
+fn test_resolve_prompt_target() {
+    let mut h = host();
+    h.host = "Web.Example.com".into();
+    assert_eq!(resolve_prompt_target(&h, ""), "deploy@web.example.com");
+
+    let resolved = "user admin\nuserknownhostsfile ~/.ssh/known_hosts\nhostname 10.0.0.5\nport 22\n";
+    assert_eq!(resolve_prompt_target(&h, resolved), "admin@10.0.0.5");
+
+    let aliased = "user deploy\nhostname 10.0.0.5\nhostkeyalias web-prod\n";
+    assert_eq!(resolve_prompt_target(&h, aliased), "deploy@web-prod");
+}
+
+#[test]
+fn test_prompt_identity_expands_home() {
+    let mut h = host();
+    assert_eq!(prompt_identity(&h), None);
+
+    h.identity_file = Some("/etc/keys/deploy".to_string());
+    assert_eq!(prompt_identity(&h).as_deref(), Some("/etc/keys/deploy"));
+
+    h.identity_file = Some("~/.ssh/deploy".to_string());
+    let expected = dirs::home_dir().unwrap().join(".ssh/deploy");
+    assert_eq!(prompt_identity(&h), Some(expected.to_string_lossy().into_owned()));
+}
+
+#[test]
+fn test_parse_openssh_version() {
+    assert_eq!(parse_openssh_version("OpenSSH_9.6p1 Ubuntu-3ubuntu13, OpenSSL 3.0.13"), Some((9, 6)));
+    assert_eq!(parse_openssh_version("OpenSSH_8.4p1, LibreSSL 2.8.3"), Some((8, 4)));
+    assert_eq!(parse_openssh_version("OpenSSH_for_Windows_8.1p1"), None);
+    assert_eq!(parse_openssh_version("Dropbear"), None);
+}
+
+#[test]
+fn test_forwards_and_tunnel_only_args() {
+    let mut h = host();
+    h.forwards = vec![
+        Forward::parse("L 5432:db:5432").unwrap(),
+        Forward::parse("D 1080").unwrap(),
+    ];
+    h.command = Some("htop".into());
+
+    let args = connect_args(&h);
+    assert!(args.windows(2).any(|w| w == ["-L", "5432:db:5432"]));
+    assert!(args.windows(2).any(|w| w == ["-D", "1080"]));
+
+    let args = tunnel_args(&h);
+    assert_eq!(args[0], "-N");
+    assert!(!args.contains(&"-t".to_string()));
+    assert!(args.windows(2).any(|w| w == ["-o", "ExitOnForwardFailure=yes"]));
+    assert!(args.windows(2).any(|w| w == ["-o", "ServerAliveInterval=15"]));
+    assert!(args.windows(2).any(|w| w == ["-L", "5432:db:5432"]));
+    assert_eq!(args.last().map(String::as_str), Some("deploy@192.0.2.10"));
+}
+
+#[test]
+fn test_scp_args() {
+    let mut h = host();
+    h.jump = Some("admin@10.0.0.1".into());
+    let upload = Transfer { direction: TransferDirection::Upload, local: "dist/app.tar".into(), remote: "/tmp/".into() };
+    let args = scp_args(&h, &upload);
+    assert!(args.windows(2).any(|w| w == ["-P", "2222"]));
+    assert!(!args.iter().any(|a| a == "-p"));
+    assert!(args.windows(2).any(|w| w == ["-o", "StrictHostKeyChecking=accept-new"]));
+    assert!(args.windows(2).any(|w| w == ["-J", "admin@10.0.0.1"]));
+    assert_eq!(args[args.len() - 2..], ["dist/app.tar", "deploy@192.0.2.10:/tmp/"]);
+
+    h.host = "2001:db8::1".into();
+    let download = Transfer { direction: TransferDirection::Download, local: String::new(), remote: "logs".into() };
+    let args = scp_args(&h, &download);
+    assert_eq!(args[args.len() - 2..], ["deploy@[2001:db8::1]:logs", "."]);
+}
+
+#[test]
+fn test_transfer_direction_parse() {
+    assert_eq!(TransferDirection::parse("Upload"), Some(TransferDirection::Upload));
+    assert_eq!(TransferDirection::parse(" d "), Some(TransferDirection::Download));
+    assert_eq!(TransferDirection::parse("sideways"), None);
+}
+
+#[test]
+fn test_master_and_sftp_args() {
+    let h = host();
+    let socket = PathBuf::from("/tmp/lazyssh.sock");
+    let master = master_args(&h, &socket);
+    assert_eq!(master[..2], ["-M", "-N"]);
+    assert!(master.windows(2).any(|w| w == ["-o", "ControlPath=/tmp/lazyssh.sock"]));
+    assert!(master.windows(2).any(|w| w == ["-p", "2222"]));
+    assert_eq!(master.last().map(String::as_str), Some("deploy@192.0.2.10"));
+
+    let sftp = sftp_args(&h, &socket);
+    assert_eq!(sftp[..3], ["-q", "-b", "-"]);
+    assert!(sftp.windows(2).any(|w| w == ["-o", "ControlMaster=no"]));
+    assert!(sftp.windows(2).any(|w| w == ["-P", "2222"]));
+    assert_eq!(sftp.last().map(String::as_str), Some("deploy@192.0.2.10"));
+}
+
+#[test]
+fn test_multiplex_and_control_args() {
+    let socket = PathBuf::from("/home/me/.lazyssh/sockets/web-1a2b3c4d");
+    assert_eq!(multiplex_args(&socket, "10m"), vec![
+        "-o", "ControlMaster=auto",
+        "-o", "ControlPath=/home/me/.lazyssh/sockets/web-1a2b3c4d",
+        "-o", "ControlPersist=10m",
+    ]);
+    assert_eq!(control_args(&host(), &socket, "exit"), vec![
+        "-O", "exit",
+        "-o", "ControlPath=/home/me/.lazyssh/sockets/web-1a2b3c4d",
+        "deploy@192.0.2.10",
+    ]);
+}
@@ -0,0 +1,82 @@
+use lazyssh::config::{Forward, Host};
+use lazyssh::tunnel::{is_port_free, listening_ports, local_port, parse_listening, TunnelManager, TunnelStatus};
+use std::net::TcpListener;
+
+#[test]
+fn test_local_port_of_forwards() {
+    let local = Forward::parse("L 5432:db:5432").unwrap();
+    assert_eq!(local_port(&local), Some(("127.0.0.1".to_string(), 5432)));
+
+    let bound = Forward::parse("L 0.0.0.0:8080:web:80").unwrap();
+    assert_eq!(local_port(&bound), Some(("0.0.0.0".to_string(), 8080)));
+
+    let dynamic = Forward::parse("D 1080").unwrap();
+    assert_eq!(local_port(&dynamic), Some(("127.0.0.1".to_string(), 1080)));
+
+    let remote = Forward::parse("R 9000:localhost:3000").unwrap();
+    assert_eq!(local_port(&remote), None);
+}
+
+#[test]
+fn test_port_checks() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = listener.local_addr().unwrap().port();
+    assert!(!is_port_free("127.0.0.1", port));
+    if let Some(ports) = listening_ports() {
+        assert!(ports.contains(&port));
+    }
+    drop(listener);
+    assert!(is_port_free("127.0.0.1", port));
+}
+
+#[test]
+fn test_parse_listening() {
+    let table = "  sl  local_address rem_address   st tx_queue rx_queue\n   0: 0100007F:1538 00000000:0000 0A 00000000:00000000\n   1: 0100007F:B36C 0100007F:1538 01 00000000:00000000\n";
+    assert_eq!(parse_listening(table).collect::<Vec<_>>(), vec![5432]);
+}
+
+#[test]
+fn test_start_fails_when_port_is_taken() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let h = Host {
+        name: "db".into(),
+        user: "u".into(),
+        host: "db.example".into(),
+        forwards: vec![Forward::parse(&format!("L {}:localhost:5432", port)).unwrap()],
+        ..Default::default()
+    };
+
+    let mut tunnels = TunnelManager::new();
+    tunnels.start(&h, std::slice::from_ref(&h));
+    let tunnel = &tunnels.tunnels()[0];
+    assert_eq!(tunnel.status, TunnelStatus::Failed(format!("127.0.0.1:{} is already in use", port)));
+    assert_eq!(tunnel.pid, None);
+}
+
+#[test]
+fn test_start_with_jump_cycle_fails_without_spawning() {
+    let a = Host {
+        name: "a".into(),
+        user: "u".into(),
+        host: "a.example".into(),
+        jump: Some("b".into()),
+        forwards: vec![Forward::parse("D 1080").unwrap()],
+        ..Default::default()
+    };
+    let b = Host { name: "b".into(), host: "b.example".into(), jump: Some("a".into()), ..Default::default() };
+    let hosts = vec![a.clone(), b];
+
+    let mut tunnels = TunnelManager::new();
+    tunnels.start(&a, &hosts);
+    tunnels.start(&a, &hosts);
+    assert_eq!(tunnels.tunnels().len(), 1);
+
+    let tunnel = &tunnels.tunnels()[0];
+    assert!(matches!(tunnel.status, TunnelStatus::Failed(_)));
+    assert_eq!(tunnel.pid, None);
+    assert!(!tunnels.is_running("a"));
+
+    tunnels.stop(0);
+    assert!(tunnels.tunnels().is_empty());
+}
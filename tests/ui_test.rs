@@ -1,5 +1,7 @@
 use lazyssh::config::Host;
-use lazyssh::ui::{Ui, FormField, AppState};
+use lazyssh::ssh::TransferDirection;
+use lazyssh::history::HostStats;
+use lazyssh::ui::{wrapped_rows, Ui, FormField, AppState, ListRow, SortMode, UNGROUPED, UNGROUPED_PATH};
 
 fn truncate(s: &str, max_len: usize) -> String {
     if s.len() <= max_len {
@@ -103,6 +105,7 @@ fn test_app_state() {
             port: Some(22),
             password: None,
             command: None,
+            ..Default::default()
         },
         Host {
             name: "test2".into(),
@@ -111,6 +114,7 @@ fn test_app_state() {
             port: Some(2222),
             password: None,
             command: None,
+            ..Default::default()
         },
     ];
     let mut app = AppState::new(hosts);
@@ -129,3 +133,299 @@ fn test_app_state() {
     assert_eq!(app.list_index, 0);
 }
 
+
+#[test]
+fn test_create_host_from_extra_fields() {
+    let fields = vec![
+        FormField { label: "Name".into(), value: "web".into(), cursor_pos: 0, is_multiline: false },
+        FormField { label: "User".into(), value: "deploy".into(), cursor_pos: 0, is_multiline: false },
+        FormField { label: "Host".into(), value: "10.0.0.1".into(), cursor_pos: 0, is_multiline: false },
+        FormField { label: "Identity".into(), value: "~/.ssh/id_web".into(), cursor_pos: 0, is_multiline: false },
+        FormField { label: "Key Only".into(), value: "yes".into(), cursor_pos: 0, is_multiline: false },
+        FormField { label: "Forwards".into(), value: "L 5432:db:5432\nnot a forward\nD 1080".into(), cursor_pos: 0, is_multiline: true },
+    ];
+    let host = Ui::create_host_from_fields(&fields);
+    assert_eq!(host.identity_file.as_deref(), Some("~/.ssh/id_web"));
+    assert!(host.identities_only);
+    assert_eq!(host.forwards.len(), 2);
+    assert_eq!(host.forwards[1].to_string(), "D 1080");
+}
+
+#[test]
+fn test_complete_identity_path() {
+    let home = std::env::temp_dir().join(format!("lazyssh-home-{}", std::process::id()));
+    let ssh_dir = home.join(".ssh");
+    std::fs::create_dir_all(ssh_dir.join("work")).unwrap();
+    for name in ["id_ed25519", "id_ed25519.pub", "id_rsa", "known_hosts", "config"] {
+        std::fs::write(ssh_dir.join(name), "").unwrap();
+    }
+
+    assert_eq!(
+        Ui::complete_identity_path("", &home),
+        vec!["~/.ssh/id_ed25519", "~/.ssh/id_rsa", "~/.ssh/work/"]
+    );
+    assert_eq!(Ui::complete_identity_path("id_e", &home), vec!["~/.ssh/id_ed25519"]);
+    assert_eq!(Ui::complete_identity_path("~/.ssh/w", &home), vec!["~/.ssh/work/"]);
+
+    let absolute = format!("{}/id_r", ssh_dir.display());
+    assert_eq!(Ui::complete_identity_path(&absolute, &home), vec![format!("{}/id_rsa", ssh_dir.display())]);
+
+    std::fs::remove_dir_all(&home).ok();
+}
+
+fn grouped(name: &str, group: Option<&str>) -> Host {
+    Host {
+        name: name.into(),
+        host: format!("{}.example", name),
+        group: group.map(String::from),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_group_tree_rows() {
+    let hosts = vec![
+        grouped("legacy", None),
+        grouped("web1", Some("prod/eu")),
+        grouped("db1", Some("prod")),
+        grouped("web2", Some("/prod//eu/")),
+    ];
+    let app = AppState::new(hosts);
+    let rows = app.rows();
+
+    let group = |path: &str, depth: usize, count: usize| ListRow::Group {
+        path: path.into(),
+        name: path.rsplit('/').next().unwrap().into(),
+        depth,
+        count,
+        collapsed: false,
+    };
+    assert_eq!(rows, vec![
+        group("prod", 0, 3),
+        group("prod/eu", 1, 2),
+        ListRow::Host { idx: 1, depth: 2 },
+        ListRow::Host { idx: 3, depth: 2 },
+        ListRow::Host { idx: 2, depth: 1 },
+        ListRow::Group { path: UNGROUPED_PATH.into(), name: UNGROUPED.into(), depth: 0, count: 1, collapsed: false },
+        ListRow::Host { idx: 0, depth: 1 },
+    ]);
+
+    // The cursor starts on the first host, not on a group header.
+    assert_eq!(app.selected_host().unwrap().name, "web1");
+    assert_eq!(app.list_index, 1);
+}
+
+#[test]
+fn test_group_named_ungrouped() {
+    let hosts = vec![
+        grouped("legacy", None),
+        grouped("old", Some("Ungrouped")),
+    ];
+    let mut app = AppState::new(hosts);
+    let names: Vec<(String, usize)> = app.rows().iter()
+        .filter_map(|row| match row {
+            ListRow::Group { name, count, .. } => Some((name.clone(), *count)),
+            ListRow::Host { .. } => None,
+        })
+        .collect();
+    assert_eq!(names, vec![(UNGROUPED.to_string(), 1), (UNGROUPED.to_string(), 1)]);
+
+    // Each node selects only its own hosts.
+    app.cursor = 0;
+    app.toggle_selection();
+    assert_eq!(app.targets(), vec![1]);
+}
+
+#[test]
+fn test_flat_list_without_groups() {
+    let app = AppState::new(vec![grouped("web", None), grouped("db", None)]);
+    assert_eq!(app.rows(), vec![ListRow::Host { idx: 0, depth: 0 }, ListRow::Host { idx: 1, depth: 0 }]);
+    assert_eq!(app.selected_host().unwrap().name, "web");
+}
+
+#[test]
+fn test_group_collapse_and_expand() {
+    let hosts = vec![
+        grouped("web1", Some("prod/eu")),
+        grouped("db1", Some("prod")),
+        grouped("legacy", None),
+    ];
+    let mut app = AppState::new(hosts);
+
+    // From a host, collapse jumps to its group, then folds it.
+    app.collapse();
+    assert!(app.selected_host().is_none());
+    app.collapse();
+    assert_eq!(app.rows().len(), 5);
+    assert!(matches!(&app.rows()[1], ListRow::Group { collapsed: true, count: 1, .. }));
+
+    app.move_next();
+    assert_eq!(app.selected_host().unwrap().name, "db1");
+    assert_eq!(app.list_index, 1);
+
+    app.move_prev();
+    app.expand();
+    assert_eq!(app.rows().len(), 6);
+
+    app.move_prev();
+    app.toggle_group();
+    assert_eq!(app.rows().len(), 3);
+    app.move_next();
+    assert!(matches!(&app.rows()[app.cursor], ListRow::Group { name, .. } if name == UNGROUPED));
+}
+
+#[test]
+fn test_filter_keeps_underlying_index() {
+    let hosts = vec![
+        grouped("web1", Some("prod")),
+        grouped("db1", Some("prod")),
+        grouped("db2", None),
+    ];
+    let mut app = AppState::new(hosts);
+
+    app.set_filter("db");
+    assert_eq!(app.rows(), vec![ListRow::Host { idx: 1, depth: 0 }, ListRow::Host { idx: 2, depth: 0 }]);
+    assert_eq!(app.selected_index(), Some(1));
+    app.move_next();
+    assert_eq!(app.selected_index(), Some(2));
+    assert_eq!(app.list_index, 2);
+
+    app.set_filter("zzz");
+    assert_eq!(app.selected_host().map(|h| h.name.as_str()), None);
+
+    // Clearing the filter returns to the tree with the same host selected.
+    app.set_filter("db2");
+    app.set_filter("");
+    assert_eq!(app.selected_host().unwrap().name, "db2");
+}
+
+#[test]
+fn test_complete_tags() {
+    let hosts = vec![
+        Host { name: "a".into(), tags: vec!["db".into(), "k8s".into()], ..Default::default() },
+        Host { name: "b".into(), tags: vec!["dev".into(), "db".into()], ..Default::default() },
+    ];
+    assert_eq!(Ui::complete_tags("d", &hosts), vec!["db", "dev"]);
+    assert_eq!(Ui::complete_tags("db, d", &hosts), vec!["db, dev"]);
+    assert_eq!(Ui::complete_tags("db k", &hosts), vec!["db k8s"]);
+    assert!(Ui::complete_tags("db, ", &hosts).is_empty());
+    assert!(Ui::complete_tags("x", &hosts).is_empty());
+}
+
+#[test]
+fn test_tags_field_roundtrip() {
+    let fields = vec![
+        FormField { label: "Name".into(), value: "web".into(), cursor_pos: 3, is_multiline: false },
+        FormField { label: "Tags".into(), value: "db, k8s legacy,,DB".into(), cursor_pos: 0, is_multiline: false },
+    ];
+    let host = Ui::create_host_from_fields(&fields);
+    assert_eq!(host.tags, vec!["db", "k8s", "legacy"]);
+}
+
+#[test]
+fn test_selection_toggle_range_and_all() {
+    let hosts = vec![
+        grouped("web1", Some("prod")),
+        grouped("web2", Some("prod")),
+        grouped("db1", Some("prod/db")),
+        grouped("dev", None),
+    ];
+    let mut app = AppState::new(hosts);
+    // Rows: prod, prod/db, db1, web1, web2, Ungrouped, dev. Cursor on db1.
+    assert_eq!(app.targets(), vec![2]);
+
+    app.toggle_selection();
+    assert_eq!(app.selection.iter().copied().collect::<Vec<_>>(), vec![2]);
+    app.move_next();
+    app.move_next();
+    app.select_range();
+    assert_eq!(app.targets(), vec![0, 1, 2]);
+
+    app.toggle_selection();
+    assert_eq!(app.targets(), vec![0, 2]);
+
+    app.clear_selection();
+    app.select_all_visible();
+    assert_eq!(app.targets(), vec![0, 1, 2, 3]);
+    app.select_all_visible();
+    assert!(app.selection.is_empty());
+
+    // Only visible hosts count: filter down to the web hosts.
+    app.set_filter("web");
+    app.select_all_visible();
+    assert_eq!(app.targets(), vec![0, 1]);
+}
+
+#[test]
+fn test_selection_toggle_group() {
+    let hosts = vec![
+        grouped("web1", Some("prod")),
+        grouped("db1", Some("prod/db")),
+        grouped("dev", None),
+    ];
+    let mut app = AppState::new(hosts);
+    app.cursor = 0;
+    app.collapse();
+    // Collapsed groups still select every host inside them.
+    app.toggle_selection();
+    assert_eq!(app.targets(), vec![0, 1]);
+    app.toggle_selection();
+    assert!(app.selection.is_empty());
+
+    app.move_next();
+    app.toggle_selection();
+    assert!(matches!(&app.rows()[app.cursor], ListRow::Group { name, .. } if name == UNGROUPED));
+    assert_eq!(app.targets(), vec![2]);
+}
+
+#[test]
+fn test_transfer_from_fields() {
+    let field = |label: &str, value: &str| FormField { label: label.into(), value: value.into(), cursor_pos: 0, is_multiline: false };
+    let transfer = Ui::transfer_from_fields(&[field("Direction", "download"), field("Local", ""), field("Remote", " /var/log/app.log ")]).unwrap();
+    assert_eq!(transfer.direction, TransferDirection::Download);
+    assert_eq!(transfer.remote, "/var/log/app.log");
+    assert_eq!(transfer.local, "");
+
+    assert!(Ui::transfer_from_fields(&[field("Direction", "upload"), field("Local", ""), field("Remote", "/tmp")]).is_none());
+    assert!(Ui::transfer_from_fields(&[field("Direction", "both"), field("Local", "a"), field("Remote", "b")]).is_none());
+}
+
+#[test]
+fn test_sort_modes() {
+    let hosts = vec![
+        grouped("web", Some("prod")),
+        grouped("api", Some("prod")),
+        grouped("db", Some("prod")),
+        grouped("Cache", None),
+    ];
+    let mut app = AppState::new(hosts);
+    app.stats.insert("web".into(), HostStats { count: 2, last: 300, last_exit: Some(0) });
+    app.stats.insert("db".into(), HostStats { count: 5, last: 100, last_exit: Some(0) });
+    app.stats.insert("Cache".into(), HostStats { count: 2, last: 200, last_exit: Some(255) });
+    let names = |app: &AppState| app.rows().iter()
+        .filter_map(|row| match row { ListRow::Host { idx, .. } => Some(app.hosts[*idx].name.clone()), _ => None })
+        .collect::<Vec<_>>();
+
+    assert_eq!(names(&app), vec!["web", "api", "db", "Cache"]);
+    assert_eq!(app.selected_host().unwrap().name, "web");
+
+    app.set_sort(SortMode::Name);
+    assert_eq!(names(&app), vec!["api", "db", "web", "Cache"]);
+    // The cursor follows the host it was on.
+    assert_eq!(app.selected_host().unwrap().name, "web");
+
+    app.set_sort(SortMode::Recent);
+    assert_eq!(names(&app), vec!["web", "db", "api", "Cache"]);
+
+    app.set_sort(SortMode::Frequent);
+    assert_eq!(names(&app), vec!["db", "web", "api", "Cache"]);
+    assert_eq!(app.sort.next(), SortMode::Config);
+}
+
+#[test]
+fn test_wrapped_rows() {
+    assert_eq!(wrapped_rows("a: Add  │  q: Quit", 80), 1);
+    assert_eq!(wrapped_rows("a: Add  │  q: Quit", 8), 2);
+    assert_eq!(wrapped_rows("abcdefghij", 4), 3);
+    assert_eq!(wrapped_rows("", 10), 1);
+}
@@ -0,0 +1,138 @@
+use lazyssh::config::{Config, Host};
+use lazyssh::vault::{is_encrypted, KdfParams, Vault, VaultError, MIN_KDF_ITERATIONS, MIN_KDF_MEMORY_KIB};
+
+const FAST: KdfParams = KdfParams { memory_kib: MIN_KDF_MEMORY_KIB, iterations: MIN_KDF_ITERATIONS };
+
+fn host_with_password(name: &str, password: Option<&str>) -> Host {
+    Host {
+        name: name.into(),
+        user: "root".into(),
+        host: "10.0.0.1".into(),
+        password: password.map(String::from),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_encrypt_decrypt_round_trip() {
+    let vault = Vault::create("correct horse", FAST).unwrap();
+    let encrypted = vault.encrypt("s3cret").unwrap();
+    assert!(is_encrypted(&encrypted));
+    assert!(!encrypted.contains("s3cret"));
+    assert_ne!(encrypted, vault.encrypt("s3cret").unwrap());
+    assert_eq!(vault.decrypt(&encrypted).unwrap(), "s3cret");
+}
+
+#[test]
+fn test_unlock_rejects_wrong_passphrase() {
+    let vault = Vault::create("correct horse", FAST).unwrap();
+    let stored: Vault = toml::from_str(&toml::to_string(&vault).unwrap()).unwrap();
+    assert!(!stored.is_unlocked());
+    assert!(stored.encrypt("x").is_none());
+
+    let mut wrong = stored.clone();
+    assert!(matches!(wrong.unlock("battery staple"), Err(VaultError::WrongPassphrase)));
+
+    let mut right = stored;
+    right.unlock("correct horse").unwrap();
+    assert_eq!(right.params(), FAST);
+}
+
+#[test]
+fn test_config_migrates_plaintext_and_unlocks() {
+    let mut config = Config {
+        hosts: vec![
+            host_with_password("a", Some("alpha")),
+            host_with_password("b", None),
+        ],
+        ..Default::default()
+    };
+    assert_eq!(config.plaintext_password_count(), 1);
+    config.set_passphrase("pass", FAST).unwrap();
+
+    let on_disk = config.to_toml().unwrap();
+    assert!(!on_disk.contains("alpha"));
+    assert!(on_disk.contains("[vault]"));
+
+    let mut loaded: Config = toml::from_str(&on_disk).unwrap();
+    assert!(loaded.is_locked());
+    assert!(loaded.unlock("nope").is_err());
+    assert!(loaded.is_locked());
+    loaded.unlock("pass").unwrap();
+    assert_eq!(loaded.hosts[0].password.as_deref(), Some("alpha"));
+    assert_eq!(loaded.hosts[1].password, None);
+}
+
+#[test]
+fn test_change_passphrase_reencrypts() {
+    let mut config = Config {
+        hosts: vec![host_with_password("a", Some("alpha"))],
+        ..Default::default()
+    };
+    config.set_passphrase("old", FAST).unwrap();
+    let mut loaded: Config = toml::from_str(&config.to_toml().unwrap()).unwrap();
+    assert!(loaded.set_passphrase("new", FAST).is_err());
+
+    loaded.unlock("old").unwrap();
+    loaded.set_passphrase("new", FAST).unwrap();
+    let mut reloaded: Config = toml::from_str(&loaded.to_toml().unwrap()).unwrap();
+    assert!(reloaded.unlock("old").is_err());
+    reloaded.unlock("new").unwrap();
+    assert_eq!(reloaded.hosts[0].password.as_deref(), Some("alpha"));
+}
+
+#[test]
+fn test_unchanged_password_keeps_ciphertext() {
+    let mut config = Config {
+        hosts: vec![host_with_password("a", Some("alpha")), host_with_password("b", Some("beta"))],
+        ..Default::default()
+    };
+    config.set_passphrase("pass", FAST).unwrap();
+    let mut loaded = Config::from_toml(&config.to_toml().unwrap()).unwrap();
+    loaded.unlock("pass").unwrap();
+    let on_disk = loaded.to_toml().unwrap();
+
+    let mut b = loaded.hosts[1].clone();
+    b.password = Some("gamma".into());
+    loaded.update_host(1, b);
+    let saved = loaded.to_toml().unwrap();
+    let line = |s: &str, n: usize| s.lines().filter(|l| l.starts_with("password")).nth(n).unwrap().to_string();
+    assert_eq!(line(&saved, 0), line(&on_disk, 0));
+    assert_ne!(line(&saved, 1), line(&on_disk, 1));
+}
+
+#[test]
+fn test_minimum_kdf_params() {
+    assert_eq!(MIN_KDF_MEMORY_KIB, 8192);
+    let min = KdfParams { memory_kib: MIN_KDF_MEMORY_KIB, iterations: MIN_KDF_ITERATIONS };
+    let vault = Vault::create("correct horse", min).unwrap();
+    assert_eq!(vault.decrypt(&vault.encrypt("s3cret").unwrap()).unwrap(), "s3cret");
+    let below = KdfParams { memory_kib: MIN_KDF_MEMORY_KIB - 1, ..min };
+    assert!(matches!(Vault::create("correct horse", below), Err(VaultError::InvalidParams(_))));
+    assert!(Vault::create("correct horse", KdfParams { memory_kib: 8, ..min }).is_err());
+
+    let old = KdfParams { memory_kib: 64, iterations: 3 };
+    assert_eq!(old.at_least_minimum(), KdfParams { memory_kib: MIN_KDF_MEMORY_KIB, iterations: 3 });
+    assert_eq!(KdfParams::default().at_least_minimum(), KdfParams::default());
+}
+
+#[test]
+fn test_foreign_ciphertext_leaves_the_vault_unlocked() {
+    let mut config = Config {
+        hosts: vec![host_with_password("a", Some("alpha")), host_with_password("b", Some("beta"))],
+        ..Default::default()
+    };
+    config.set_passphrase("pass", FAST).unwrap();
+    let mut loaded = Config::from_toml(&config.to_toml().unwrap()).unwrap();
+    let foreign = Vault::create("other", FAST).unwrap().encrypt("beta").unwrap();
+    loaded.hosts[1].password = Some(foreign.clone());
+
+    match loaded.unlock("pass") {
+        Err(VaultError::CorruptedPasswords(hosts)) => assert_eq!(hosts, vec!["b".to_string()]),
+        other => panic!("expected corrupted passwords, got {:?}", other),
+    }
+    assert!(!loaded.is_locked());
+    assert_eq!(loaded.hosts[0].password.as_deref(), Some("alpha"));
+    assert_eq!(loaded.hosts[1].password.as_deref(), Some(foreign.as_str()));
+    assert!(loaded.to_toml().unwrap().contains(&foreign));
+}